@@ -1,30 +1,137 @@
+use crate::config::{
+    AbsentValue, BytesType, CacheConfig, Config, DateTimeType, DecimalType, DecoratorProfile,
+    DtoNullability, EntityStyle, Equality, ExportStyle, FindAndCountStrategy, ImportGroup,
+    IndentStyle, ModelConfig, ModuleSystem, OverwritePolicy, PrismaAccess, RetryConfig,
+    ScopeConfig,
+};
 use crate::parser::{Field, Model};
 use core::fmt;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt::Write as FmtWrite;
+use std::hash::{Hash, Hasher};
 use std::io::Write as IoWrite;
 use std::{fs, path::Path};
 
-const ENTITY_PATH: &str = "domain/entity/";
+const ENTITY_PATH: &str = "domain/entity";
 const MAPPER_PATH: &str = "infra/database/prisma/mappers";
 const REPOSITORY_PATH: &str = "app/repositories";
 const PRISMA_REPOSITORY_PATH: &str = "infra/database/prisma";
+const PRISMA_SERVICE_PATH: &str = "infra/database/prisma";
+const DTO_PATH: &str = "app/dtos";
+const SCHEMA_PATH: &str = "domain/schemas";
+const MIKRO_ORM_ENTITY_PATH: &str = "infra/database/mikro-orm/entities";
+const EVENTS_PATH: &str = "domain/events";
+const CONSTANTS_PATH: &str = "domain/constants";
+const CONTEXTS_PATH: &str = "modules";
 
-#[derive(Debug, PartialEq, Eq)]
+const TYPES_BARREL_FILE: &str = "types.generated.ts";
+const REPORT_FILE: &str = "entity-generator.report.json";
+const REPOSITORY_REGISTRY_FILE: &str = "repository.registry.ts";
+const SOFT_DELETE_EXTENSION_FILE: &str = "soft-delete.extension.ts";
+
+const CUSTOM_CODE_START: &str = "// <custom>";
+const CUSTOM_CODE_END: &str = "// </custom>";
+
+const AGGREGATE_OPTIONS_TYPE: &str = "{ sum?: K[]; avg?: K[]; min?: K[]; max?: K[] }";
+const AGGREGATE_RESULT_TYPE: &str =
+    "Record<'sum' | 'avg' | 'min' | 'max', Partial<Record<K, number | null>>>";
+
+const MANAGED_FIELDS: &[&str] = &["id", "createdAt", "updatedAt", "deletedAt"];
+
+/// Property names that can't be declared on a generated entity class.
+const RESERVED_PROPERTY_NAMES: &[&str] = &[
+    "constructor",
+    "prototype",
+    "__proto__",
+    "toString",
+    "toLocaleString",
+    "toJSON",
+    "valueOf",
+    "hasOwnProperty",
+    "isPrototypeOf",
+    "propertyIsEnumerable",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModuleType {
     Entity,
     Mapper,
     Repository(Option<Vec<RepositoryOperations>>),
     PrismaRepository,
+    PrismaService,
+    QueryDto,
+    Dto,
+    JsonSchema,
+    MikroOrmEntity,
+    IntegrationSpec,
+    Events,
+    Constants,
+}
+
+/// A set of modules generated under one module path. Several targets can be
+/// written in the same run, e.g. the application code and a test directory.
+#[derive(Debug)]
+pub struct OutputTarget {
+    pub modules: Vec<ModuleType>,
+    pub module_path: String,
+}
+
+const MODULE_TYPE_NAMES: &[&str] = &[
+    "Entity",
+    "Mapper",
+    "Repository",
+    "Prisma repository",
+    "Prisma service",
+    "Query DTO",
+    "DTO",
+    "JSON Schema",
+    "MikroORM entity",
+    "Integration spec",
+    "Events",
+    "Constants",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseModuleTypeError {
+    value: String,
+}
+
+impl fmt::Display for ParseModuleTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown module type \"{}\", expected one of: {}",
+            self.value,
+            MODULE_TYPE_NAMES.join(", ")
+        )
+    }
 }
 
-impl From<&str> for ModuleType {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for ModuleType {
+    type Error = ParseModuleTypeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "Entity" => ModuleType::Entity,
-            "Mapper" => ModuleType::Mapper,
-            "Repository" => ModuleType::Repository(None),
-            "Prisma repository" => ModuleType::PrismaRepository,
-            _ => unreachable!(),
+            "Entity" => Ok(ModuleType::Entity),
+            "Mapper" => Ok(ModuleType::Mapper),
+            "Repository" => Ok(ModuleType::Repository(None)),
+            "Prisma repository" => Ok(ModuleType::PrismaRepository),
+            "Prisma service" => Ok(ModuleType::PrismaService),
+            "Query DTO" => Ok(ModuleType::QueryDto),
+            "DTO" | "Dto" => Ok(ModuleType::Dto),
+            "JSON Schema" => Ok(ModuleType::JsonSchema),
+            "MikroORM entity" => Ok(ModuleType::MikroOrmEntity),
+            "Integration spec" => Ok(ModuleType::IntegrationSpec),
+            "Events" => Ok(ModuleType::Events),
+            "Constants" => Ok(ModuleType::Constants),
+            _ => Err(ParseModuleTypeError {
+                value: value.to_string(),
+            }),
         }
     }
 }
@@ -36,10 +143,51 @@ impl From<ModuleType> for &str {
             ModuleType::Mapper => "Mapper",
             ModuleType::Repository(_) => "Repository",
             ModuleType::PrismaRepository => "Prisma repository",
+            ModuleType::PrismaService => "Prisma service",
+            ModuleType::QueryDto => "Query DTO",
+            ModuleType::Dto => "DTO",
+            ModuleType::JsonSchema => "JSON Schema",
+            ModuleType::MikroOrmEntity => "MikroORM entity",
+            ModuleType::IntegrationSpec => "Integration spec",
+            ModuleType::Events => "Events",
+            ModuleType::Constants => "Constants",
+        }
+    }
+}
+
+fn export_keyword(config: &Config) -> &'static str {
+    match config.export_style {
+        ExportStyle::Named => "export",
+        ExportStyle::Default => "export default",
+    }
+}
+
+impl DecoratorProfile {
+    /// Decorator registering a class with the DI container.
+    fn injectable(&self) -> &'static str {
+        match self {
+            DecoratorProfile::NestJs => "@Injectable()\n",
+            DecoratorProfile::Plain => "",
+        }
+    }
+
+    /// Decorator resolving a constructor parameter by injection token.
+    fn inject(&self, token: &str) -> String {
+        match self {
+            DecoratorProfile::NestJs => format!("@Inject({}) ", token),
+            DecoratorProfile::Plain => String::new(),
         }
     }
 }
 
+fn uppercase_first_char(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().collect::<String>() + c.as_str(),
+    }
+}
+
 fn lowercase_first_char(s: &str) -> String {
     let mut c = s.chars();
     match c.next() {
@@ -51,459 +199,7399 @@ fn lowercase_first_char(s: &str) -> String {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RepositoryOperations {
     Create,
+    CreateMany,
     Find,
     FindMany,
+    FindAndCount,
+    Count,
     Delete,
     Update,
+    Upsert,
+    DeleteMany,
+    UpdateMany,
+    Search,
+    Exists,
+    FindByUnique,
+    Increment,
+    FindRandom,
+    FindManyStream,
+    Aggregate,
+    Restore,
 }
 
 impl fmt::Display for RepositoryOperations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
             RepositoryOperations::Create => write!(f, "create"),
+            RepositoryOperations::CreateMany => write!(f, "createMany"),
             RepositoryOperations::Find => write!(f, "find"),
             RepositoryOperations::FindMany => write!(f, "findMany"),
+            RepositoryOperations::FindAndCount => write!(f, "findAndCount"),
+            RepositoryOperations::Count => write!(f, "count"),
             RepositoryOperations::Delete => write!(f, "delete"),
             RepositoryOperations::Update => write!(f, "update"),
+            RepositoryOperations::Upsert => write!(f, "upsert"),
+            RepositoryOperations::DeleteMany => write!(f, "deleteMany"),
+            RepositoryOperations::UpdateMany => write!(f, "updateMany"),
+            RepositoryOperations::Search => write!(f, "search"),
+            RepositoryOperations::Exists => write!(f, "exists"),
+            RepositoryOperations::FindByUnique => write!(f, "findBy<unique field>"),
+            RepositoryOperations::Increment => write!(f, "increment"),
+            RepositoryOperations::FindRandom => write!(f, "findRandom"),
+            RepositoryOperations::FindManyStream => write!(f, "findManyStream"),
+            RepositoryOperations::Aggregate => write!(f, "aggregate"),
+            RepositoryOperations::Restore => write!(f, "restore"),
         }
     }
 }
 
-fn build_repository_methods(
-    model_name: &str,
-    input_type: &str,
-    return_type: &str,
-    has_mapper: bool,
-    op: &RepositoryOperations,
-) -> String {
-    match op {
-        RepositoryOperations::Create => {
-            let mut method = format!(
-                "async create(data: {}): Promise<{}> {{\n",
-                input_type, return_type
-            );
-            if has_mapper {
-                write!(
-                    method,
-                    r#"    const result = await this.prisma.{}.create({{
-      data,
-    }})
-
-    return {}Mapper.toDomain(result)
-  }}"#,
-                    lowercase_first_char(model_name),
-                    model_name
-                )
-                .unwrap();
-
-                return method;
-            }
-
-            write!(
-                method,
-                r#"      return this.prisma.{}.create({{
-        data,
-      }})
-  }}"#,
-                lowercase_first_char(model_name)
-            )
-            .unwrap();
-
-            method
-        }
-        RepositoryOperations::Delete => format!(
-            r#"async delete(id: string) {{
-    await this.prisma.{}.update({{
-      where: {{
-        id,
-      }},
-      data: {{
-        deletedAt: new Date(),
-      }},
-    }})
-  }}"#,
-            lowercase_first_char(model_name)
-        ),
-        RepositoryOperations::Find => {
-            let mut method = format!(
-                "async find(data: {}): Promise<{}> {{\n",
-                input_type, return_type
-            );
-
-            if has_mapper {
-                write!(
-                    method,
-                    r#"    const result = await this.prisma.{}.findFirst({{
-      where: data,
-    }})
-
-    return {}Mapper.toDomain(result)
-  }}"#,
-                    lowercase_first_char(model_name),
-                    model_name
-                )
-                .unwrap();
+/// Fields marked with a `/// @tag` schema comment or listed for the model in
+/// the config.
+fn annotated_fields<'a>(
+    model: &'a Model,
+    config: &Config,
+    tag: &str,
+    configured: fn(&ModelConfig) -> &Vec<String>,
+) -> Vec<&'a Field> {
+    let configured = config.model(&model.name).map(configured);
 
-                return method;
-            }
+    model
+        .fields
+        .iter()
+        .filter(|field| {
+            field.has_tag(tag) || configured.is_some_and(|names| names.contains(&field.name))
+        })
+        .collect()
+}
 
-            write!(
-                method,
-                r#"      return this.prisma.{}.findFirst({{
-        where: data,
-      }})
-  }}"#,
-                lowercase_first_char(model_name)
-            )
-            .unwrap();
+fn searchable_fields<'a>(model: &'a Model, config: &Config) -> Vec<&'a Field> {
+    annotated_fields(model, config, "@searchable", |model_config| {
+        &model_config.searchable_fields
+    })
+    .into_iter()
+    .filter(|field| field.field_type == "String" && !field.is_list)
+    .collect()
+}
 
-            method
-        }
-        RepositoryOperations::FindMany => {
-            let mut method = format!(
-                "async findMany(data: {}): Promise<{}[]> {{\n",
-                input_type, return_type
-            );
+fn sensitive_fields<'a>(model: &'a Model, config: &Config) -> Vec<&'a Field> {
+    annotated_fields(model, config, "@sensitive", |model_config| {
+        &model_config.sensitive_fields
+    })
+    .into_iter()
+    .filter(|field| field_type_name(field, config).is_some())
+    .collect()
+}
 
-            if has_mapper {
-                write!(
-                    method,
-                    r#"    const result = await this.prisma.{}.findMany({{
-      where: data,
-    }})
-
-    return result.map({}Mapper.toDomain)
-  }}"#,
-                    lowercase_first_char(model_name),
-                    model_name
-                )
-                .unwrap();
+/// Fields `findMany` selects by default, leaving out any whose entity
+/// property is renamed since the selected row keeps the column name.
+fn select_fields<'a>(model: &'a Model, config: &Config) -> Vec<&'a Field> {
+    annotated_fields(model, config, "@select", |model_config| {
+        &model_config.select_fields
+    })
+    .into_iter()
+    .filter(|field| {
+        field_type_name(field, config).is_some()
+            && !RESERVED_PROPERTY_NAMES.contains(&field.name.as_str())
+    })
+    .collect()
+}
 
-                return method;
-            }
+fn unique_fields<'a>(model: &'a Model, config: &Config) -> Vec<&'a Field> {
+    model
+        .fields
+        .iter()
+        .filter(|field| {
+            field.attribute("unique").is_some()
+                && !field.is_list
+                && field_type_name(field, config).is_some()
+        })
+        .collect()
+}
 
-            write!(
-                method,
-                r#"      return this.prisma.{}.findMany({{
-        where: data,
-      }})
-  }}"#,
-                lowercase_first_char(model_name)
+fn numeric_fields(model: &Model) -> Vec<&Field> {
+    model
+        .fields
+        .iter()
+        .filter(|field| field.name != "id" && !field.is_list)
+        .filter(|field| {
+            matches!(
+                field.field_type.as_str(),
+                "Int" | "Float" | "Decimal" | "BigInt"
             )
-            .unwrap();
+        })
+        .collect()
+}
 
-            method
-        }
-        RepositoryOperations::Update => {
-            let mut method = format!(
-                "async update(id: string, data: {}): Promise<{}> {{\n",
-                input_type, return_type
-            );
+fn numeric_field_union(model: &Model) -> String {
+    numeric_fields(model)
+        .iter()
+        .map(|field| format!("'{}'", field.name))
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
 
-            if has_mapper {
-                write!(
-                    method,
-                    r#"    const result = await this.prisma.{}.update({{
-      where: {{
-        id,
-      }},
-      data,
-    }})
-
-    return {}Mapper.toDomain(result)
-  }}"#,
-                    lowercase_first_char(model_name),
-                    model_name
-                )
-                .unwrap();
+fn is_soft_delete(model: &Model) -> bool {
+    model.fields.iter().any(|field| field.name == "deletedAt")
+}
 
-                return method;
-            }
+/// Whether the repository queries filter out soft-deleted rows themselves,
+/// rather than leaving it to the generated client extension.
+fn filters_soft_deleted(model: &Model, config: &Config) -> bool {
+    is_soft_delete(model) && config.soft_delete_extension.is_none()
+}
 
-            write!(
-                method,
-                r#"      return this.prisma.{}.findMany({{
-        where: data,
-      }})
-  }}"#,
-                lowercase_first_char(model_name)
-            )
-            .unwrap();
+fn is_supported(model: &Model, op: &RepositoryOperations, config: &Config) -> bool {
+    // Views are read-only.
+    if model.is_view && is_mutation(op) {
+        return false;
+    }
 
-            method
+    match op {
+        RepositoryOperations::Search => !searchable_fields(model, config).is_empty(),
+        RepositoryOperations::FindByUnique => !unique_fields(model, config).is_empty(),
+        RepositoryOperations::Increment | RepositoryOperations::Aggregate => {
+            !numeric_fields(model).is_empty()
         }
+        RepositoryOperations::Restore => is_soft_delete(model),
+        _ => true,
     }
 }
 
-fn create_repository(
-    model: &Model,
-    methods: Option<Vec<RepositoryOperations>>,
+struct RepositoryContext<'a> {
+    model: &'a Model,
+    config: &'a Config,
+    class_name: String,
+    scope: Option<&'a ScopeConfig>,
+    input_type: String,
+    filter_type: String,
+    return_type: String,
     has_mapper: bool,
-    has_entity: bool,
-) -> (String, String) {
-    let mut abstract_repository = format!("export abstract class {}Repository {{", model.name);
-    let mut prisma_repository = format!(
-        r#"@Injectable()
-export class Prisma{}Repository implements {}Repository {{
-    constructor(private readonly prisma: PrismaService) {{}}"#,
-        model.name, model.name
-    );
+}
 
-    let (input_type, return_type) = if has_entity {
-        (format!("Partial<{}>", model.name), model.name.clone())
-    } else {
-        ("any".to_string(), "any".to_string())
-    };
+impl RepositoryContext<'_> {
+    /// Entity-shaped input converted to Prisma's field names and types by the
+    /// mapper.
+    fn persisted(&self, value: &str) -> String {
+        if !self.has_mapper {
+            return value.to_string();
+        }
 
-    let methods = methods.unwrap_or_default();
+        format!("{}Mapper.toPartialPersistence({})", self.class_name, value)
+    }
 
-    for method in &methods {
-        match method {
-            RepositoryOperations::Create => write!(
-                abstract_repository,
-                "\n\t\tabstract create(data: {}): Promise<{}>",
-                input_type, return_type
-            )
-            .unwrap(),
-            RepositoryOperations::Find => write!(
-                abstract_repository,
-                "\n\t\tabstract find(data: {}): Promise<{}>",
-                input_type, return_type
-            )
-            .unwrap(),
-            RepositoryOperations::FindMany => write!(
-                abstract_repository,
-                "\n\t\tabstract findMany(data: {}): Promise<{}[]>",
-                input_type, return_type
-            )
-            .unwrap(),
-            RepositoryOperations::Update => write!(
-                abstract_repository,
-                "\n\t\tabstract update(id: string, data: {}): Promise<{}>",
-                input_type, return_type
-            )
-            .unwrap(),
-            RepositoryOperations::Delete => write!(
-                abstract_repository,
-                "\n\t\tabstract delete(id: string): Promise<void>"
-            )
-            .unwrap(),
+    /// Object property named after `value`, converted by the mapper.
+    fn persisted_property(&self, value: &str) -> String {
+        if !self.has_mapper {
+            return value.to_string();
         }
 
-        write!(
-            prisma_repository,
-            "\n\t\t{}",
-            build_repository_methods(&model.name, &input_type, &return_type, has_mapper, method)
-        )
-        .unwrap();
+        format!("{}: {}", value, self.persisted(value))
     }
 
-    write!(abstract_repository, "\n}}").unwrap();
-    write!(prisma_repository, "\n}}").unwrap();
+    /// Wraps a read query in the generated retry helper when retries are
+    /// configured.
+    fn read(&self, query: String) -> String {
+        if self.config.retry.is_some() {
+            return format!("this.withRetry(() => {})", query);
+        }
 
-    (abstract_repository, prisma_repository)
-}
+        query
+    }
 
-fn create_mapper(model: &Model) -> String {
-    let mut mapper = String::new();
-    write!(
-        mapper,
-        "export class {}Mapper {{\n\tstatic toDomain(data: Prisma{}): {} {{\n\t\treturn new {}({{",
-        model.name, model.name, model.name, model.name
-    )
-    .unwrap();
+    /// Like `read`, but served from the injected cache manager under `key`
+    /// when caching is configured.
+    fn cached_read(&self, key: String, query: String) -> String {
+        let query = self.read(query);
 
-    for field in &model.fields {
-        if get_field_with_type(field, false).is_some() {
-            match field.field_type.as_str() {
-                "Decimal" | "BigInt" => write!(
-                    mapper,
-                    "\n\t\t\t{}: Number(data.{}),",
-                    field.name, field.name
-                )
-                .unwrap(),
-                _ => write!(mapper, "\n\t\t\t{}: data.{},", field.name, field.name).unwrap(),
-            }
+        // Scoped reads are cached per scope so one tenant never sees another's
+        // cached rows.
+        let key = match self.scope {
+            Some(_) => format!("${{JSON.stringify(this.scope)}}:{}", key),
+            None => key,
+        };
+
+        match &self.config.cache {
+            Some(cache) => format!(
+                "this.cached(`{}:{}`, () => {})",
+                cache_key_prefix(self.model, cache),
+                key,
+                query
+            ),
+            None => query,
         }
     }
 
-    write!(mapper, "\n\t\t}})\n\t}}\n}}").unwrap();
-
-    mapper
-}
+    /// Prisma client, read through the overridable `client` getter when
+    /// configured.
+    fn client(&self) -> &'static str {
+        match self.config.prisma_access {
+            PrismaAccess::Field => "this.prisma",
+            PrismaAccess::Getter => "this.client",
+        }
+    }
 
-fn create_entity(model: &Model) -> String {
-    let entity_interface = String::from("I") + &model.name;
-    let mut entity = String::new();
+    /// Prisma model delegate of the client.
+    fn delegate(&self) -> String {
+        format!(
+            "{}.{}",
+            self.client(),
+            lowercase_first_char(&self.model.name)
+        )
+    }
 
-    write!(entity, "export interface {} {{", entity_interface).unwrap();
+    /// Input converted by the mapper, with the model's base scope merged in.
+    fn scoped_value(&self, value: &str) -> String {
+        match self.scope {
+            Some(_) => format!("{{ ...{}, ...this.scope }}", self.persisted(value)),
+            None => self.persisted(value),
+        }
+    }
 
-    for field in &model.fields {
-        let parsed_field_option = get_field_with_type(field, false);
+    /// Like `scoped_value`, also leaving out soft-deleted rows.
+    fn live_value(&self, value: &str) -> String {
+        if !filters_soft_deleted(self.model, self.config) {
+            return self.scoped_value(value);
+        }
 
-        if let Some(parsed_field) = parsed_field_option {
-            entity.push_str(&parsed_field);
+        match self.scope {
+            Some(_) => format!(
+                "{{ ...{}, ...this.scope, deletedAt: null }}",
+                self.persisted(value)
+            ),
+            None => format!("{{ ...{}, deletedAt: null }}", self.persisted(value)),
         }
     }
 
-    entity.push_str("\n}\n\n");
-
-    write!(
-        entity,
-        "export class {} implements {} {{",
-        model.name, entity_interface
-    )
-    .unwrap();
+    /// Object property named after `value`, converted by the mapper and with
+    /// the base scope merged in.
+    fn scoped_property(&self, value: &str) -> String {
+        match self.scope {
+            Some(_) => format!("{}: {}", value, self.scoped_value(value)),
+            None => self.persisted_property(value),
+        }
+    }
 
-    for field in &model.fields {
-        let parsed_field_option = get_field_with_type(field, true);
-        if let Some(parsed_field) = parsed_field_option {
-            entity.push_str(&parsed_field);
+    /// Base scope spread into a multi-line object literal, `depth` levels
+    /// deep.
+    fn scope_entry(&self, depth: usize) -> String {
+        match self.scope {
+            Some(_) => format!("\n{}...this.scope,", self.config.indent_style.indent(depth)),
+            None => String::new(),
         }
     }
 
-    let param_name = lowercase_first_char(&model.name);
+    /// Like `scoped_property`, also leaving out soft-deleted rows.
+    fn live_property(&self, value: &str) -> String {
+        if !filters_soft_deleted(self.model, self.config) {
+            return self.scoped_property(value);
+        }
 
-    writeln!(
-        entity,
-        "\n\n\tconstructor({}: {}) {{\n\t\tObject.assign(this, {})\n\t}}\n}}",
-        param_name, entity_interface, param_name,
-    )
-    .unwrap();
+        format!("{}: {}", value, self.live_value(value))
+    }
 
-    entity
-}
+    /// Like `scope_entry`, also leaving out soft-deleted rows.
+    fn live_entry(&self, depth: usize) -> String {
+        if !filters_soft_deleted(self.model, self.config) {
+            return self.scope_entry(depth);
+        }
 
-fn build_type_string(
-    field_type: &str,
-    field_name: &str,
-    is_optional: bool,
-    read_only: bool,
-) -> String {
-    let mut formatted_field_type = String::new();
-    if read_only {
-        write!(
-            formatted_field_type,
-            "\n\treadonly {}: {}",
-            field_name, field_type
+        format!(
+            "{}\n{}deletedAt: null,",
+            self.scope_entry(depth),
+            self.config.indent_style.indent(depth)
         )
-        .unwrap();
-    } else {
-        write!(formatted_field_type, "\n\t{}: {}", field_name, field_type).unwrap();
-    };
-
-    if is_optional {
-        write!(formatted_field_type, " | null").unwrap();
     }
 
-    formatted_field_type
-}
+    /// Filter on the base scope and the live rows alone, if there's any.
+    fn live_filter(&self) -> Option<&'static str> {
+        match (self.scope, filters_soft_deleted(self.model, self.config)) {
+            (Some(_), true) => Some("{ ...this.scope, deletedAt: null }"),
+            (Some(_), false) => Some("this.scope"),
+            (None, true) => Some("{ deletedAt: null }"),
+            (None, false) => None,
+        }
+    }
 
-fn get_field_with_type(field: &Field, read_only: bool) -> Option<String> {
-    match field.field_type.as_str() {
-        "Float" | "Int" | "Decimal" | "BigInt" => Some(build_type_string(
-            "number",
-            &field.name,
-            field.is_optional,
-            read_only,
-        )),
-        "String" => Some(build_type_string(
-            "string",
-            &field.name,
-            field.is_optional,
-            read_only,
-        )),
-        "Boolean" => Some(build_type_string(
-            "boolean",
-            &field.name,
-            field.is_optional,
-            read_only,
-        )),
-        "DateTime" => Some(build_type_string(
-            "Date",
-            &field.name,
-            field.is_optional,
-            read_only,
-        )),
-        _ => None,
+    /// `where` property limited to the base scope and the live rows.
+    fn live_where(&self, depth: usize) -> String {
+        match self.live_filter() {
+            Some(filter) => format!(
+                "\n{}where: {},",
+                self.config.indent_style.indent(depth),
+                filter
+            ),
+            None => String::new(),
+        }
     }
-}
 
-fn to_kebab_case(name: &str) -> String {
-    let mut kebab_case_string = String::new();
-    for (i, ch) in name.chars().enumerate() {
-        if ch.is_uppercase() && i > 0 {
-            kebab_case_string.push('-');
+    /// Type of the Prisma rows, or of the entity interface in contracts,
+    /// which can't depend on the Prisma client types.
+    fn row_type(&self) -> String {
+        if self.config.contracts_only {
+            return format!("I{}", self.class_name);
         }
-        kebab_case_string.push(ch.to_ascii_lowercase());
+
+        format!("Prisma{}", self.model.name)
     }
 
-    kebab_case_string
-}
+    /// Optional `findMany` parameters enabled in the config.
+    fn find_many_parameters(&self) -> String {
+        let mut parameters = String::new();
 
-fn build_path(dir: &Path, module_path: &str, module_type: ModuleType, model_name: &str) -> String {
-    let kebab_model_name = to_kebab_case(model_name);
-    let (path, file_name) = match module_type {
-        ModuleType::Entity => (ENTITY_PATH, format!("{}.entity.ts", kebab_model_name)),
-        ModuleType::Mapper => (MAPPER_PATH, format!("{}.mapper.ts", kebab_model_name)),
-        ModuleType::Repository(_) => (
-            REPOSITORY_PATH,
-            format!("{}.repository.ts", kebab_model_name),
-        ),
-        ModuleType::PrismaRepository => (
-            PRISMA_REPOSITORY_PATH,
-            format!("prisma-{}.repository.ts", kebab_model_name),
+        let fields_type = self.row_type();
+
+        if self.config.find_many.distinct {
+            write!(parameters, ", distinct?: (keyof {})[]", fields_type).unwrap();
+        }
+
+        if self.config.find_many.pagination {
+            parameters.push_str(", pagination?: { skip?: number; take?: number }");
+        }
+
+        if self.config.find_many.order_by {
+            write!(
+                parameters,
+                ", orderBy?: Partial<Record<keyof {}, 'asc' | 'desc'>>",
+                fields_type
+            )
+            .unwrap();
+        }
+
+        if self.config.find_many.sort {
+            write!(
+                parameters,
+                ", sort?: {{ field: keyof {}; direction: 'asc' | 'desc' }}[]",
+                fields_type
+            )
+            .unwrap();
+        }
+
+        parameters
+    }
+
+    /// Element type returned by `findMany`, narrowed to the selected fields
+    /// when the model has a default selection.
+    fn find_many_type(&self) -> String {
+        let fields = select_fields(self.model, self.config);
+
+        if fields.is_empty() || self.return_type == "any" {
+            return self.return_type.clone();
+        }
+
+        let keys: Vec<String> = fields
+            .iter()
+            .map(|field| format!("'{}'", field.name))
+            .collect();
+
+        // The selected rows are returned unmapped, so they're typed as rows.
+        format!("Pick<{}, {}>", self.row_type(), keys.join(" | "))
+    }
+
+    /// Prisma query options forwarding the optional `findMany` parameters.
+    fn find_many_options(&self, depth: usize) -> String {
+        let mut options = String::new();
+        let indent = self.config.indent_style.indent(depth);
+
+        let fields = select_fields(self.model, self.config);
+
+        if !fields.is_empty() {
+            write!(options, "\n{}select: {{", indent).unwrap();
+
+            for field in fields {
+                write!(
+                    options,
+                    "\n{}{}: true,",
+                    self.config.indent_style.indent(depth + 1),
+                    field.name
+                )
+                .unwrap();
+            }
+
+            write!(options, "\n{}}},", indent).unwrap();
+        }
+
+        if self.config.find_many.distinct {
+            write!(options, "\n{}distinct,", indent).unwrap();
+        }
+
+        if self.config.find_many.pagination {
+            write!(options, "\n{}...pagination,", indent).unwrap();
+        }
+
+        let sort = "sort?.map(({ field, direction }) => ({ [field]: direction }))";
+
+        match (self.config.find_many.order_by, self.config.find_many.sort) {
+            (true, true) => write!(options, "\n{}orderBy: {} ?? orderBy,", indent, sort).unwrap(),
+            (true, false) => write!(options, "\n{}orderBy,", indent).unwrap(),
+            (false, true) => write!(options, "\n{}orderBy: {},", indent, sort).unwrap(),
+            (false, false) => {}
+        }
+
+        options
+    }
+
+    /// Prisma action for a single-row lookup, switched to its throwing
+    /// variant when `throwOnNotFound` is set.
+    fn lookup_action(&self, action: &str) -> String {
+        if self.config.throw_on_not_found {
+            return format!("{}OrThrow", action);
+        }
+
+        action.to_string()
+    }
+
+    /// Return type of a lookup that can miss. `any` already covers `null`.
+    fn nullable_type(&self) -> String {
+        if self.return_type == "any" {
+            return self.return_type.clone();
+        }
+
+        format!("{} | null", self.return_type)
+    }
+
+    /// Return type of a single-row lookup, which is only nullable when the
+    /// lookup doesn't throw.
+    fn lookup_type(&self) -> String {
+        if self.config.throw_on_not_found {
+            return self.return_type.clone();
+        }
+
+        self.nullable_type()
+    }
+
+    fn id_type(&self) -> String {
+        id_type(self.model, self.config)
+    }
+
+    fn map_lookup(&self) -> String {
+        if self.config.throw_on_not_found {
+            return format!("{}Mapper.toDomain(result)", self.class_name);
+        }
+
+        format!("result ? {}Mapper.toDomain(result) : null", self.class_name)
+    }
+
+    /// `@Cacheable` decorator for a read method, or `@CacheEvict` for a
+    /// mutation, when cache decorators are configured.
+    fn cache_decorator(&self, op: &RepositoryOperations, method_name: &str) -> String {
+        let style = self.config.indent_style;
+        let Some(decorators) = &self.config.cache_decorators else {
+            return String::new();
+        };
+
+        let prefix = decorators
+            .key_prefix
+            .clone()
+            .unwrap_or_else(|| lowercase_first_char(&self.model.name));
+
+        // Random picks shouldn't be repeated, and async generators can't be
+        // cached as a single value.
+        if is_read_operation(op)
+            && !matches!(
+                op,
+                RepositoryOperations::FindRandom | RepositoryOperations::FindManyStream
+            )
+        {
+            let ttl = decorators
+                .ttl
+                .map(|ttl| format!(", ttl: {}", ttl))
+                .unwrap_or_default();
+
+            return format!(
+                "@Cacheable({{ key: (...args: unknown[]) => `{}:{}:${{JSON.stringify(args)}}`{} }})\n{}",
+                prefix,
+                method_name,
+                ttl,
+                style.indent(1)
+            );
+        }
+
+        if is_mutation(op) {
+            return format!(
+                "@CacheEvict({{ key: '{}:*' }})\n{}",
+                prefix,
+                style.indent(1)
+            );
+        }
+
+        String::new()
+    }
+
+    /// Wraps a mutation so it evicts the model's cached reads once it
+    /// completes.
+    fn write(&self, query: String) -> String {
+        if self.config.cache.is_some() {
+            return format!("this.evicting(() => {})", query);
+        }
+
+        query
+    }
+
+    /// Name and payload interface of the event announcing a mutation, when
+    /// events are configured.
+    fn event(&self, op: &RepositoryOperations) -> Option<(String, String)> {
+        self.config.events.as_ref()?;
+        let event = mutation_event(op)?;
+
+        Some((
+            format!(
+                "{}.{}",
+                lowercase_first_char(&self.model.name),
+                event.to_lowercase()
+            ),
+            format!("{}{}Event", self.class_name, event),
+        ))
+    }
+
+    /// Body of a mutation resolving to the written row, which is emitted to
+    /// the event listeners first when events are configured.
+    fn written_result(&self, op: &RepositoryOperations, query: String) -> String {
+        let style = self.config.indent_style;
+        let mapped = format!("{}Mapper.toDomain(result)", self.class_name);
+
+        let Some((name, event)) = self.event(op) else {
+            if self.has_mapper {
+                return format!(
+                    "const result = await {}\n\n{}return {}",
+                    query,
+                    style.indent(2),
+                    mapped
+                );
+            }
+
+            return format!("return {}", query);
+        };
+
+        let variable = lowercase_first_char(&self.class_name);
+        let assignment = if self.has_mapper {
+            format!(
+                "const result = await {}\n{}const {} = {}",
+                query,
+                style.indent(2),
+                variable,
+                mapped
+            )
+        } else {
+            format!("const {} = await {}", variable, query)
+        };
+
+        format!(
+            "{}\n\n{}this.events.emit('{}', {{ {} }} satisfies {})\n\n{}return {}",
+            assignment,
+            style.indent(2),
+            name,
+            variable,
+            event,
+            style.indent(2),
+            variable
+        )
+    }
+
+    /// Emits the event of a mutation addressed by `id`, when events are
+    /// configured.
+    fn emit_by_id(&self, op: &RepositoryOperations) -> String {
+        let style = self.config.indent_style;
+
+        self.event(op)
+            .map(|(name, event)| {
+                format!(
+                    "\n\n{}this.events.emit('{}', {{ id }} satisfies {})",
+                    style.indent(2),
+                    name,
+                    event
+                )
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Past tense of a mutation, naming the event it emits.
+fn mutation_event(op: &RepositoryOperations) -> Option<&'static str> {
+    match op {
+        RepositoryOperations::Create => Some("Created"),
+        RepositoryOperations::Update | RepositoryOperations::Increment => Some("Updated"),
+        RepositoryOperations::Delete => Some("Deleted"),
+        RepositoryOperations::Restore => Some("Restored"),
+        // Prisma doesn't tell whether an upsert created the row, and the bulk
+        // mutations only return a count, so there's no payload to emit.
+        _ => None,
+    }
+}
+
+fn is_mutation(op: &RepositoryOperations) -> bool {
+    matches!(
+        op,
+        RepositoryOperations::Create
+            | RepositoryOperations::CreateMany
+            | RepositoryOperations::Update
+            | RepositoryOperations::Upsert
+            | RepositoryOperations::Delete
+            | RepositoryOperations::DeleteMany
+            | RepositoryOperations::UpdateMany
+            | RepositoryOperations::Increment
+            | RepositoryOperations::Restore
+    )
+}
+
+fn is_bulk_mutation(op: &RepositoryOperations) -> bool {
+    matches!(
+        op,
+        RepositoryOperations::DeleteMany | RepositoryOperations::UpdateMany
+    )
+}
+
+fn cache_key_prefix(model: &Model, cache: &CacheConfig) -> String {
+    cache
+        .key_prefix
+        .clone()
+        .unwrap_or_else(|| lowercase_first_char(&model.name))
+}
+
+fn build_cache_helpers(model: &Model, cache: &CacheConfig, style: IndentStyle) -> String {
+    // cache-manager v5 takes the TTL in milliseconds and can't list its keys,
+    // so the keys set under the prefix are tracked in an entry of their own.
+    let keys_key = format!("'{}:keys'", cache_key_prefix(model, cache));
+
+    format!(
+        r#"private async cached<T>(key: string, query: () => Promise<T>): Promise<T> {{
+{}const cached = await this.cache.get<T>(key)
+{}if (cached != null) {{
+{}return cached
+{}}}
+
+{}const result = await query()
+{}const keys = (await this.cache.get<string[]>({})) ?? []
+
+{}await this.cache.set(key, result, {})
+{}await this.cache.set({}, [...new Set([...keys, key])], {})
+
+{}return result
+{}}}
+
+{}private async evicting<T>(query: () => Promise<T>): Promise<T> {{
+{}const result = await query()
+{}const keys = (await this.cache.get<string[]>({})) ?? []
+
+{}await Promise.all([...keys, {}].map((key) => this.cache.del(key)))
+
+{}return result
+{}}}"#,
+        style.indent(2),
+        style.indent(2),
+        style.indent(3),
+        style.indent(2),
+        style.indent(2),
+        style.indent(2),
+        keys_key,
+        style.indent(2),
+        cache.ttl_ms,
+        style.indent(2),
+        keys_key,
+        cache.ttl_ms,
+        style.indent(2),
+        style.indent(1),
+        style.indent(1),
+        style.indent(2),
+        style.indent(2),
+        keys_key,
+        style.indent(2),
+        keys_key,
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+/// The model's configured base scope, when every scoped field exists on it.
+fn base_scope<'a>(model: &Model, config: &'a Config) -> Option<&'a ScopeConfig> {
+    config
+        .model(&model.name)
+        .and_then(|model_config| model_config.scope.as_ref())
+        .filter(|scope| {
+            !scope.fields.is_empty()
+                && scope
+                    .fields
+                    .iter()
+                    .all(|name| model.fields.iter().any(|field| &field.name == name))
+        })
+}
+
+fn build_scope_getter(scope: &ScopeConfig, style: IndentStyle) -> String {
+    let entries: String = scope
+        .fields
+        .iter()
+        .map(|field| format!("\n{}{}: this.context.{},", style.indent(3), field, field))
+        .collect();
+
+    format!(
+        r#"private get scope() {{
+{}return {{{}
+{}}}
+{}}}"#,
+        style.indent(2),
+        entries,
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+fn is_read_operation(op: &RepositoryOperations) -> bool {
+    matches!(
+        op,
+        RepositoryOperations::Find
+            | RepositoryOperations::FindMany
+            | RepositoryOperations::FindAndCount
+            | RepositoryOperations::Count
+            | RepositoryOperations::Search
+            | RepositoryOperations::Exists
+            | RepositoryOperations::FindByUnique
+            | RepositoryOperations::FindRandom
+            | RepositoryOperations::FindManyStream
+            | RepositoryOperations::Aggregate
+    )
+}
+
+/// Guard run on the `where` of bulk mutations. It's protected so repositories
+/// can override it with stricter rules.
+fn build_sanitize_where_helper(style: IndentStyle) -> String {
+    format!(
+        r#"protected sanitizeWhere<T extends object>(operation: string, where: T, options?: {{ allowEmptyWhere?: boolean }}): T {{
+{}const conditions = Object.values(where).filter((value) => value !== undefined)
+
+{}if (conditions.length === 0 && !options?.allowEmptyWhere) {{
+{}throw new Error(`${{operation}} called with an empty where, which would affect every row. Pass {{ allowEmptyWhere: true }} to allow it.`)
+{}}}
+
+{}return where
+{}}}"#,
+        style.indent(2),
+        style.indent(2),
+        style.indent(3),
+        style.indent(2),
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+fn build_retry_helper(retry: &RetryConfig, style: IndentStyle) -> String {
+    format!(
+        r#"private async withRetry<T>(query: () => Promise<T>): Promise<T> {{
+{}for (let attempt = 1; ; attempt++) {{
+{}try {{
+{}return await query()
+{}}} catch (error) {{
+{}const code = (error as {{ code?: string }}).code
+{}if (attempt >= {} || !['P1001', 'P1017'].includes(code ?? '')) {{
+{}throw error
+{}}}
+
+{}await new Promise((resolve) => setTimeout(resolve, {} * attempt))
+{}}}
+{}}}
+{}}}"#,
+        style.indent(2),
+        style.indent(3),
+        style.indent(4),
+        style.indent(3),
+        style.indent(4),
+        style.indent(4),
+        retry.attempts,
+        style.indent(5),
+        style.indent(4),
+        style.indent(4),
+        retry.backoff_ms,
+        style.indent(3),
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+fn build_unique_finder(ctx: &RepositoryContext, field: &Field) -> String {
+    let style = ctx.config.indent_style;
+    let RepositoryContext {
+        model,
+        config,
+        has_mapper,
+        ..
+    } = ctx;
+    let mut method = format!(
+        "async findBy{}({}: {}): Promise<{}> {{\n",
+        uppercase_first_char(&field.name),
+        field.name,
+        property_type(model, field, config).unwrap(),
+        ctx.lookup_type()
+    );
+
+    // findUnique can't filter on non-unique columns such as deletedAt or the
+    // base scope, so those models fall back to findFirst. The soft-delete
+    // extension only intercepts findFirst as well.
+    let mut filter = String::new();
+    if filters_soft_deleted(model, config) {
+        write!(filter, "\n{}deletedAt: null,", style.indent(4)).unwrap();
+    }
+    filter.push_str(&ctx.scope_entry(4));
+
+    let action = if filter.is_empty() && !is_soft_delete(model) {
+        "findUnique"
+    } else {
+        "findFirst"
+    };
+
+    if *has_mapper {
+        write!(
+            method,
+            r#"{}const result = await {}
+
+{}return {}
+{}}}"#,
+            style.indent(2),
+            ctx.cached_read(
+                format!("{}:${{{}}}", field.name, field.name),
+                format!(
+                    r#"{}.{}({{
+{}where: {{
+{}{},{}
+{}}},
+{}}})"#,
+                    ctx.delegate(),
+                    ctx.lookup_action(action),
+                    style.indent(3),
+                    style.indent(4),
+                    field.name,
+                    filter,
+                    style.indent(3),
+                    style.indent(2)
+                )
+            ),
+            style.indent(2),
+            ctx.map_lookup(),
+            style.indent(1)
+        )
+        .unwrap();
+
+        return method;
+    }
+
+    write!(
+        method,
+        r#"{}return {}
+{}}}"#,
+        style.indent(2),
+        ctx.cached_read(
+            format!("{}:${{{}}}", field.name, field.name),
+            format!(
+                r#"{}.{}({{
+{}where: {{
+{}{},{}
+{}}},
+{}}})"#,
+                ctx.delegate(),
+                ctx.lookup_action(action),
+                style.indent(3),
+                style.indent(4),
+                field.name,
+                filter,
+                style.indent(3),
+                style.indent(2)
+            )
         ),
+        style.indent(1)
+    )
+    .unwrap();
+
+    method
+}
+
+/// `findAndCount` returning a page of rows with the total number of matching
+/// rows. On Postgres it can fetch both in a single raw query, counting the
+/// rows with a window function, instead of two queries in a transaction.
+fn build_find_and_count(ctx: &RepositoryContext) -> String {
+    let style = ctx.config.indent_style;
+    let RepositoryContext {
+        model,
+        config,
+        filter_type,
+        return_type,
+        has_mapper,
+        class_name,
+        ..
+    } = ctx;
+
+    let signature = format!(
+        "async findAndCount(data: {}, pagination?: {{ skip?: number; take?: number }}): Promise<{{ items: {}[]; total: number }}>",
+        filter_type, return_type
+    );
+
+    if !uses_window_function(model, config) {
+        let items = if *has_mapper {
+            format!("result.map({}Mapper.toDomain)", class_name)
+        } else {
+            "result".to_string()
+        };
+
+        return format!(
+            r#"{} {{
+{}const [result, total] = await {}
+
+{}return {{ items: {}, total }}
+{}}}"#,
+            signature,
+            style.indent(2),
+            ctx.read(format!(
+                r#"{}.$transaction([
+{}{}.findMany({{
+{}where: {},
+{}...pagination,
+{}}}),
+{}{}.count({{
+{}where: {},
+{}}}),
+{}])"#,
+                ctx.client(),
+                style.indent(3),
+                ctx.delegate(),
+                style.indent(4),
+                ctx.live_value("data"),
+                style.indent(4),
+                style.indent(3),
+                style.indent(3),
+                ctx.delegate(),
+                style.indent(4),
+                ctx.live_value("data"),
+                style.indent(3),
+                style.indent(2)
+            )),
+            style.indent(2),
+            items,
+            style.indent(1)
+        );
+    }
+
+    let fields: Vec<&Field> = model
+        .fields
+        .iter()
+        .filter(|field| field_type_name(field, config).is_some())
+        .collect();
+
+    // The filter is keyed by Prisma field, while the query needs the column
+    // behind it.
+    let columns: Vec<String> = fields
+        .iter()
+        .map(|field| format!("{}: '{}'", field.name, field.column_name()))
+        .collect();
+
+    // Columns are selected under the field names Prisma Client would return.
+    let mut selection: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.column_name() == field.name {
+                format!("\"{}\"", field.name)
+            } else {
+                format!("\"{}\" AS \"{}\"", field.column_name(), field.name)
+            }
+        })
+        .collect();
+    selection.push("COUNT(*) OVER() AS \"total\"".to_string());
+
+    // Raw queries bypass the soft-delete client extension, so they leave the
+    // soft-deleted rows out themselves.
+    let filter = if is_soft_delete(model) && !filters_soft_deleted(model, config) {
+        format!("{{ ...{}, deletedAt: null }}", ctx.scoped_value("data"))
+    } else {
+        ctx.live_value("data")
     };
 
-    format!("{}/{}{}/{}", dir.display(), module_path, path, file_name)
+    let items = if *has_mapper {
+        format!(
+            "rows.map(({{ total, ...row }}) => {}Mapper.toDomain(row))",
+            class_name
+        )
+    } else {
+        "rows.map(({ total, ...row }) => row)".to_string()
+    };
+
+    format!(
+        r#"{} {{
+{}const columns: Record<string, string> = {{ {} }}
+{}const conditions = Object.entries({})
+{}.filter(([, value]) => value !== undefined)
+{}.map(([key, value]) => {{
+{}const column = Prisma.raw(`"${{columns[key]}}"`)
+
+{}return value === null ? Prisma.sql`${{column}} IS NULL` : Prisma.sql`${{column}} = ${{value}}`
+{}}})
+
+{}// The window count is taken before OFFSET and LIMIT apply, so every row
+{}// carries the total number of matching rows.
+{}const rows = await {}
+
+{}return {{
+{}items: {},
+{}total: Number(rows[0]?.total ?? 0),
+{}}}
+{}}}"#,
+        signature,
+        style.indent(2),
+        columns.join(", "),
+        style.indent(2),
+        filter,
+        style.indent(3),
+        style.indent(3),
+        style.indent(4),
+        style.indent(4),
+        style.indent(3),
+        style.indent(2),
+        style.indent(2),
+        style.indent(2),
+        ctx.read(format!(
+            r#"{}.$queryRaw<(Prisma{} & {{ total: bigint }})[]>`
+{}SELECT {}
+{}FROM "{}"
+{}${{conditions.length > 0 ? Prisma.sql`WHERE ${{Prisma.join(conditions, ' AND ')}}` : Prisma.empty}}
+{}OFFSET ${{pagination?.skip ?? 0}}
+{}LIMIT ${{pagination?.take ?? null}}
+{}`"#,
+            ctx.client(),
+            model.name,
+            style.indent(3),
+            selection.join(", "),
+            style.indent(3),
+            model.table_name(),
+            style.indent(3),
+            style.indent(3),
+            style.indent(3),
+            style.indent(2)
+        )),
+        style.indent(2),
+        style.indent(3),
+        items,
+        style.indent(3),
+        style.indent(2),
+        style.indent(1)
+    )
 }
 
-fn write_to_module<P: AsRef<Path>>(path: P, contents: String) -> std::io::Result<()> {
-    if let Some(parent) = path.as_ref().parent() {
-        fs::create_dir_all(parent)?;
+/// Whether `findAndCount` is generated as a single raw query with a window
+/// count, which is only valid SQL on Postgres.
+fn uses_window_function(model: &Model, config: &Config) -> bool {
+    matches!(config.find_and_count, FindAndCountStrategy::WindowFunction)
+        && model.provider.as_deref() == Some("postgresql")
+}
+
+/// Delete method named `name`, running `query` against the row with the
+/// given id.
+fn build_delete(
+    ctx: &RepositoryContext,
+    op: &RepositoryOperations,
+    name: &str,
+    query: String,
+) -> String {
+    let style = ctx.config.indent_style;
+
+    if !ctx.config.delete_returns_entity {
+        return format!(
+            r#"async {}(id: {}) {{
+{}await {}{}
+{}}}"#,
+            name,
+            ctx.id_type(),
+            style.indent(2),
+            query,
+            ctx.emit_by_id(op),
+            style.indent(1)
+        );
     }
 
-    let mut file = fs::File::create(path)?;
-    file.write_all(contents.as_bytes())?;
+    let result = if ctx.has_mapper {
+        format!("{}Mapper.toDomain(result)", ctx.class_name)
+    } else {
+        "result".to_string()
+    };
 
-    Ok(())
+    if ctx.has_mapper || ctx.config.events.is_some() {
+        return format!(
+            r#"async {}(id: {}): Promise<{}> {{
+{}const result = await {}{}
+
+{}return {}
+{}}}"#,
+            name,
+            ctx.id_type(),
+            ctx.return_type,
+            style.indent(2),
+            query,
+            ctx.emit_by_id(op),
+            style.indent(2),
+            result,
+            style.indent(1)
+        );
+    }
+
+    format!(
+        r#"async {}(id: {}): Promise<{}> {{
+{}return {}
+{}}}"#,
+        name,
+        ctx.id_type(),
+        ctx.return_type,
+        style.indent(2),
+        query,
+        style.indent(1)
+    )
 }
 
-pub fn write_modules(modules: Vec<ModuleType>, dir: &Path, module_path: &str, model: &Model) {
-    for module in &modules {
-        match module {
-            ModuleType::Entity => write_to_module(
-                build_path(dir, module_path, ModuleType::Entity, &model.name),
-                create_entity(model),
-            )
-            .unwrap(),
-            ModuleType::Mapper => write_to_module(
-                build_path(dir, module_path, ModuleType::Mapper, &model.name),
-                create_mapper(model),
-            )
-            .unwrap(),
-            ModuleType::Repository(methods) => {
-                let (abstract_repository, prisma_repository) = create_repository(
-                    model,
-                    methods.clone(),
-                    modules.contains(&ModuleType::Mapper),
-                    modules.contains(&ModuleType::Entity),
-                );
+fn build_repository_methods(ctx: &RepositoryContext, op: &RepositoryOperations) -> String {
+    let style = ctx.config.indent_style;
+    let RepositoryContext {
+        model,
+        config,
+        input_type,
+        filter_type,
+        return_type,
+        has_mapper,
+        ..
+    } = ctx;
+    let has_mapper = *has_mapper;
+    let class_name = &ctx.class_name;
 
-                write_to_module(
-                    build_path(dir, module_path, ModuleType::Repository(None), &model.name),
-                    abstract_repository,
+    match op {
+        RepositoryOperations::Create => format!(
+            r#"async create(data: {}): Promise<{}> {{
+{}{}
+{}}}"#,
+            input_type,
+            return_type,
+            style.indent(2),
+            ctx.written_result(
+                op,
+                ctx.write(format!(
+                    r#"{}.create({{
+{}{},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    ctx.scoped_property("data"),
+                    style.indent(2)
+                ))
+            ),
+            style.indent(1)
+        ),
+        // createMany doesn't return the rows, so there is nothing to map.
+        RepositoryOperations::CreateMany => format!(
+            r#"async createMany(data: {}[]): Promise<{{ count: number }}> {{
+{}return {}
+{}}}"#,
+            input_type,
+            style.indent(2),
+            ctx.write(format!(
+                r#"{}.createMany({{
+{}data{},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                match (ctx.scope, has_mapper) {
+                    (Some(_), _) => format!(": data.map((item) => ({}))", ctx.scoped_value("item")),
+                    (None, true) =>
+                        format!(": data.map({}Mapper.toPartialPersistence)", class_name),
+                    (None, false) => String::new(),
+                },
+                style.indent(2)
+            )),
+            style.indent(1)
+        ),
+        RepositoryOperations::Delete => {
+            let hard_delete = ctx.write(format!(
+                r#"{}.delete({{
+{}where: {{
+{}id,{}
+{}}},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                style.indent(4),
+                ctx.scope_entry(4),
+                style.indent(3),
+                style.indent(2)
+            ));
+
+            if !is_soft_delete(model) {
+                return build_delete(ctx, op, "delete", hard_delete);
+            }
+
+            // Models with a `deletedAt` column can be deleted either way, so
+            // both methods are decorated one by one.
+            let soft_delete = ctx.write(format!(
+                r#"{}.update({{
+{}where: {{
+{}id,{}
+{}}},
+{}data: {{
+{}deletedAt: new Date(),
+{}}},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                style.indent(4),
+                ctx.scope_entry(4),
+                style.indent(3),
+                style.indent(3),
+                style.indent(4),
+                style.indent(3),
+                style.indent(2)
+            ));
+
+            [("softDelete", soft_delete), ("hardDelete", hard_delete)]
+                .into_iter()
+                .map(|(name, query)| {
+                    format!(
+                        "{}{}",
+                        ctx.cache_decorator(op, name),
+                        build_delete(ctx, op, name, query)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(&format!("\n{}", style.indent(1)))
+        }
+        RepositoryOperations::Find => {
+            let mut method = format!(
+                "async find(data: {}): Promise<{}> {{\n",
+                filter_type,
+                ctx.lookup_type()
+            );
+
+            if has_mapper {
+                write!(
+                    method,
+                    r#"{}const result = await {}
+
+{}return {}
+{}}}"#,
+                    style.indent(2),
+                    ctx.cached_read(
+                        "find:${JSON.stringify(data)}".to_string(),
+                        format!(
+                            r#"{}.{}({{
+{}where: {},
+{}}})"#,
+                            ctx.delegate(),
+                            ctx.lookup_action("findFirst"),
+                            style.indent(3),
+                            ctx.live_value("data"),
+                            style.indent(2)
+                        )
+                    ),
+                    style.indent(2),
+                    ctx.map_lookup(),
+                    style.indent(1)
                 )
                 .unwrap();
 
-                write_to_module(
-                    build_path(dir, module_path, ModuleType::PrismaRepository, &model.name),
-                    prisma_repository,
+                return method;
+            }
+
+            write!(
+                method,
+                r#"{}return {}
+{}}}"#,
+                style.indent(2),
+                ctx.cached_read(
+                    "find:${JSON.stringify(data)}".to_string(),
+                    format!(
+                        r#"{}.{}({{
+{}where: {},
+{}}})"#,
+                        ctx.delegate(),
+                        ctx.lookup_action("findFirst"),
+                        style.indent(3),
+                        ctx.live_value("data"),
+                        style.indent(2)
+                    )
+                ),
+                style.indent(1)
+            )
+            .unwrap();
+
+            method
+        }
+        RepositoryOperations::FindMany => {
+            let mut method = format!(
+                "async findMany(data: {}{}): Promise<{}[]> {{\n",
+                filter_type,
+                ctx.find_many_parameters(),
+                ctx.find_many_type()
+            );
+
+            // A partial row can't be mapped to an entity, so selected rows
+            // are returned as they are.
+            if has_mapper && select_fields(model, ctx.config).is_empty() {
+                write!(
+                    method,
+                    r#"{}const result = await {}
+
+{}return result.map({}Mapper.toDomain)
+{}}}"#,
+                    style.indent(2),
+                    ctx.read(format!(
+                        r#"{}.findMany({{
+{}where: {},{}
+{}}})"#,
+                        ctx.delegate(),
+                        style.indent(3),
+                        ctx.live_value("data"),
+                        ctx.find_many_options(3),
+                        style.indent(2)
+                    )),
+                    style.indent(2),
+                    class_name,
+                    style.indent(1)
                 )
                 .unwrap();
+
+                return method;
             }
-            _ => unreachable!(),
+
+            write!(
+                method,
+                r#"{}return {}
+{}}}"#,
+                style.indent(2),
+                ctx.read(format!(
+                    r#"{}.findMany({{
+{}where: {},{}
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    ctx.live_value("data"),
+                    ctx.find_many_options(3),
+                    style.indent(2)
+                )),
+                style.indent(1)
+            )
+            .unwrap();
+
+            method
         }
+        RepositoryOperations::FindAndCount => build_find_and_count(ctx),
+        RepositoryOperations::Update => format!(
+            r#"async update(id: {}, data: {}): Promise<{}> {{
+{}{}
+{}}}"#,
+            ctx.id_type(),
+            input_type,
+            return_type,
+            style.indent(2),
+            ctx.written_result(
+                op,
+                ctx.write(format!(
+                    r#"{}.update({{
+{}where: {{
+{}id,{}
+{}}},
+{}{},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    style.indent(4),
+                    ctx.scope_entry(4),
+                    style.indent(3),
+                    style.indent(3),
+                    ctx.persisted_property("data"),
+                    style.indent(2)
+                ))
+            ),
+            style.indent(1)
+        ),
+        RepositoryOperations::Upsert => format!(
+            r#"async upsert(id: {}, data: {}): Promise<{}> {{
+{}{}
+{}}}"#,
+            ctx.id_type(),
+            input_type,
+            return_type,
+            style.indent(2),
+            ctx.written_result(
+                op,
+                ctx.write(format!(
+                    r#"{}.upsert({{
+{}where: {{
+{}id,{}
+{}}},
+{}create: {},
+{}update: {},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    style.indent(4),
+                    ctx.scope_entry(4),
+                    style.indent(3),
+                    style.indent(3),
+                    ctx.scoped_value("data"),
+                    style.indent(3),
+                    ctx.persisted("data"),
+                    style.indent(2)
+                ))
+            ),
+            style.indent(1)
+        ),
+        RepositoryOperations::DeleteMany => {
+            // The guard only sees the caller's filter, as the base scope
+            // alone would still match every row of the tenant.
+            let where_clause = ctx.live_value("this.sanitizeWhere('deleteMany', where, options)");
+            let query = if is_soft_delete(model) {
+                format!(
+                    r#"{}.updateMany({{
+{}where: {},
+{}data: {{
+{}deletedAt: new Date(),
+{}}},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    where_clause,
+                    style.indent(3),
+                    style.indent(4),
+                    style.indent(3),
+                    style.indent(2)
+                )
+            } else {
+                format!(
+                    r#"{}.deleteMany({{
+{}where: {},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    where_clause,
+                    style.indent(2)
+                )
+            };
+
+            format!(
+                r#"async deleteMany(where: {}, options?: {{ allowEmptyWhere?: boolean }}): Promise<number> {{
+{}const {{ count }} = await {}
+
+{}return count
+{}}}"#,
+                filter_type,
+                style.indent(2),
+                ctx.write(query),
+                style.indent(2),
+                style.indent(1)
+            )
+        }
+        RepositoryOperations::UpdateMany => format!(
+            r#"async updateMany(where: {}, data: {}, options?: {{ allowEmptyWhere?: boolean }}): Promise<number> {{
+{}const {{ count }} = await {}
+
+{}return count
+{}}}"#,
+            filter_type,
+            input_type,
+            style.indent(2),
+            ctx.write(format!(
+                r#"{}.updateMany({{
+{}where: {},
+{}{},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                ctx.live_value("this.sanitizeWhere('updateMany', where, options)"),
+                style.indent(3),
+                ctx.persisted_property("data"),
+                style.indent(2)
+            )),
+            style.indent(2),
+            style.indent(1)
+        ),
+        RepositoryOperations::Search => {
+            let mut method = format!(
+                "async search(query: string, pagination?: {{ skip?: number; take?: number }}): Promise<{}[]> {{\n",
+                return_type
+            );
+
+            let conditions: String = searchable_fields(model, config)
+                .iter()
+                .map(|field| {
+                    format!(
+                        "\n{}{{ {}: {{ contains: query }} }},",
+                        style.indent(5),
+                        field.name
+                    )
+                })
+                .collect();
+
+            if has_mapper {
+                write!(
+                    method,
+                    r#"{}const result = await {}
+
+{}return result.map({}Mapper.toDomain)
+{}}}"#,
+                    style.indent(2),
+                    ctx.read(format!(
+                        r#"{}.findMany({{
+{}where: {{
+{}OR: [{}
+{}],{}
+{}}},
+{}...pagination,
+{}}})"#,
+                        ctx.delegate(),
+                        style.indent(3),
+                        style.indent(4),
+                        conditions,
+                        style.indent(4),
+                        ctx.live_entry(4),
+                        style.indent(3),
+                        style.indent(3),
+                        style.indent(2)
+                    )),
+                    style.indent(2),
+                    class_name,
+                    style.indent(1)
+                )
+                .unwrap();
+
+                return method;
+            }
+
+            write!(
+                method,
+                r#"{}return {}
+{}}}"#,
+                style.indent(2),
+                ctx.read(format!(
+                    r#"{}.findMany({{
+{}where: {{
+{}OR: [{}
+{}],{}
+{}}},
+{}...pagination,
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    style.indent(4),
+                    conditions,
+                    style.indent(4),
+                    ctx.live_entry(4),
+                    style.indent(3),
+                    style.indent(3),
+                    style.indent(2)
+                )),
+                style.indent(1)
+            )
+            .unwrap();
+
+            method
+        }
+        RepositoryOperations::Exists => format!(
+            r#"async exists(where: {}): Promise<boolean> {{
+{}const count = await {}
+
+{}return count > 0
+{}}}"#,
+            filter_type,
+            style.indent(2),
+            ctx.read(format!(
+                r#"{}.count({{
+{}{},
+{}take: 1,
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                ctx.live_property("where"),
+                style.indent(3),
+                style.indent(2)
+            )),
+            style.indent(2),
+            style.indent(1)
+        ),
+        RepositoryOperations::FindByUnique => unique_fields(model, config)
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}{}",
+                    ctx.cache_decorator(
+                        op,
+                        &format!("findBy{}", uppercase_first_char(&field.name))
+                    ),
+                    build_unique_finder(ctx, field)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(&format!("\n{}", style.indent(1))),
+        RepositoryOperations::Increment => format!(
+            r#"async increment<K extends {}>(id: {}, field: K, by: number): Promise<{}> {{
+{}{}
+{}}}"#,
+            numeric_field_union(model),
+            ctx.id_type(),
+            return_type,
+            style.indent(2),
+            ctx.written_result(
+                op,
+                ctx.write(format!(
+                    r#"{}.update({{
+{}where: {{
+{}id,{}
+{}}},
+{}data: {{
+{}[field]: {{ increment: by }},
+{}}},
+{}}})"#,
+                    ctx.delegate(),
+                    style.indent(3),
+                    style.indent(4),
+                    ctx.scope_entry(4),
+                    style.indent(3),
+                    style.indent(3),
+                    style.indent(4),
+                    style.indent(3),
+                    style.indent(2)
+                ))
+            ),
+            style.indent(1)
+        ),
+        RepositoryOperations::FindRandom => {
+            let mut method = format!(
+                r#"async findRandom(): Promise<{}> {{
+{}const total = await {}
+
+{}if (total === 0) {{
+{}return null
+{}}}
+"#,
+                ctx.nullable_type(),
+                style.indent(2),
+                ctx.read(format!(
+                    "{}.count({})",
+                    ctx.delegate(),
+                    ctx.live_filter()
+                        .map(|filter| format!("{{ where: {} }}", filter))
+                        .unwrap_or_default()
+                )),
+                style.indent(2),
+                style.indent(3),
+                style.indent(2)
+            );
+
+            if has_mapper {
+                write!(
+                    method,
+                    r#"
+{}const result = await {}
+
+{}return result ? {}Mapper.toDomain(result) : null
+{}}}"#,
+                    style.indent(2),
+                    ctx.read(format!(
+                        r#"{}.findFirst({{{}
+{}skip: Math.floor(Math.random() * total),
+{}}})"#,
+                        ctx.delegate(),
+                        ctx.live_where(3),
+                        style.indent(3),
+                        style.indent(2)
+                    )),
+                    style.indent(2),
+                    class_name,
+                    style.indent(1)
+                )
+                .unwrap();
+
+                return method;
+            }
+
+            write!(
+                method,
+                r#"
+{}return {}
+{}}}"#,
+                style.indent(2),
+                ctx.read(format!(
+                    r#"{}.findFirst({{{}
+{}skip: Math.floor(Math.random() * total),
+{}}})"#,
+                    ctx.delegate(),
+                    ctx.live_where(3),
+                    style.indent(3),
+                    style.indent(2)
+                )),
+                style.indent(1)
+            )
+            .unwrap();
+
+            method
+        }
+        RepositoryOperations::Aggregate => format!(
+            r#"async aggregate<K extends {}>(where: {}, options: {}): Promise<{}> {{
+{}const select = (keys?: K[]) => keys && Object.fromEntries(keys.map((key) => [key, true]))
+{}// Decimal and BigInt aggregates aren't plain numbers, so every value is
+{}// converted to a number.
+{}const values = (group: Record<string, unknown> | null | undefined) =>
+{}Object.fromEntries(
+{}Object.entries(group ?? {{}}).map(([key, value]) => [key, value == null ? null : Number(value)]),
+{}) as Partial<Record<K, number | null>>
+
+{}const result = await {}
+
+{}return {{
+{}sum: values(result._sum),
+{}avg: values(result._avg),
+{}min: values(result._min),
+{}max: values(result._max),
+{}}}
+{}}}"#,
+            numeric_field_union(model),
+            filter_type,
+            AGGREGATE_OPTIONS_TYPE,
+            AGGREGATE_RESULT_TYPE,
+            style.indent(2),
+            style.indent(2),
+            style.indent(2),
+            style.indent(2),
+            style.indent(3),
+            style.indent(4),
+            style.indent(3),
+            style.indent(2),
+            ctx.read(format!(
+                r#"{}.aggregate({{
+{}{},
+{}_sum: select(options.sum),
+{}_avg: select(options.avg),
+{}_min: select(options.min),
+{}_max: select(options.max),
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                ctx.live_property("where"),
+                style.indent(3),
+                style.indent(3),
+                style.indent(3),
+                style.indent(3),
+                style.indent(2)
+            )),
+            style.indent(2),
+            style.indent(3),
+            style.indent(3),
+            style.indent(3),
+            style.indent(3),
+            style.indent(2),
+            style.indent(1)
+        ),
+        RepositoryOperations::Count => format!(
+            r#"async count(data: {}): Promise<number> {{
+{}return {}
+{}}}"#,
+            filter_type,
+            style.indent(2),
+            ctx.read(format!(
+                r#"{}.count({{
+{}where: {},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                ctx.live_value("data"),
+                style.indent(2)
+            )),
+            style.indent(1)
+        ),
+        RepositoryOperations::Restore => format!(
+            r#"async restore(id: {}) {{
+{}await {}{}
+{}}}"#,
+            ctx.id_type(),
+            style.indent(2),
+            ctx.write(format!(
+                r#"{}.update({{
+{}where: {{
+{}id,{}
+{}}},
+{}data: {{
+{}deletedAt: null,
+{}}},
+{}}})"#,
+                ctx.delegate(),
+                style.indent(3),
+                style.indent(4),
+                ctx.scope_entry(4),
+                style.indent(3),
+                style.indent(3),
+                style.indent(4),
+                style.indent(3),
+                style.indent(2)
+            )),
+            ctx.emit_by_id(op),
+            style.indent(1)
+        ),
+        RepositoryOperations::FindManyStream => format!(
+            r#"async *findManyStream(data: {}): AsyncGenerator<{}> {{
+{}const batchSize = {}
+
+{}for (let skip = 0; ; skip += batchSize) {{
+{}const batch = await {}
+
+{}yield* {}
+
+{}if (batch.length < batchSize) {{
+{}return
+{}}}
+{}}}
+{}}}"#,
+            filter_type,
+            return_type,
+            style.indent(2),
+            config.stream_batch_size(),
+            style.indent(2),
+            style.indent(3),
+            ctx.read(format!(
+                r#"{}.findMany({{
+{}where: {},
+{}skip,
+{}take: batchSize,
+{}}})"#,
+                ctx.delegate(),
+                style.indent(4),
+                ctx.live_value("data"),
+                style.indent(4),
+                style.indent(4),
+                style.indent(3)
+            )),
+            style.indent(3),
+            if has_mapper {
+                format!("batch.map({}Mapper.toDomain)", class_name)
+            } else {
+                "batch".to_string()
+            },
+            style.indent(3),
+            style.indent(4),
+            style.indent(3),
+            style.indent(2),
+            style.indent(1)
+        ),
+    }
+}
+
+fn create_repository(
+    model: &Model,
+    methods: Option<Vec<RepositoryOperations>>,
+    has_mapper: bool,
+    has_entity: bool,
+    config: &Config,
+) -> (String, String) {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+    let mut abstract_repository = format!(
+        "{} abstract class {}Repository {{",
+        export_keyword(config),
+        class_name
+    );
+
+    let scope = base_scope(model, config);
+
+    // Decorator, name and type of each constructor parameter, shared by the
+    // constructor and the factory.
+    let mut parameters = vec![(String::new(), "prisma", "PrismaService".to_string())];
+    if let Some(scope) = scope {
+        parameters.push((String::new(), "context", scope.context.clone()));
+    }
+    if config.cache.is_some() {
+        parameters.push((
+            config.decorators.inject("CACHE_MANAGER"),
+            "cache",
+            "Cache".to_string(),
+        ));
+    }
+    if config.events.is_some() {
+        parameters.push((String::new(), "events", "EventEmitter2".to_string()));
+    }
+
+    let constructor_parameters = parameters
+        .iter()
+        .map(|(decorator, name, parameter_type)| {
+            format!("{}private readonly {}: {}", decorator, name, parameter_type)
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let mut prisma_repository = format!(
+        r#"{}{} class Prisma{}Repository implements {}Repository {{
+{}constructor({}) {{}}"#,
+        config.decorators.injectable(),
+        export_keyword(config),
+        class_name,
+        class_name,
+        style.indent(1),
+        constructor_parameters
+    );
+
+    // Contracts are typed against the entity interface, since the class isn't
+    // part of a contracts package.
+    let entity_type = if config.contracts_only {
+        format!("I{}", class_name)
+    } else {
+        class_name.clone()
+    };
+
+    let (input_type, return_type) = if has_entity {
+        (format!("Partial<{}>", entity_type), entity_type.clone())
+    } else {
+        ("any".to_string(), "any".to_string())
+    };
+
+    // Partial<Json> can't express Prisma's Json filter operators, so Json
+    // fields are left out of the where filter type.
+    let json_fields: Vec<String> = model
+        .fields
+        .iter()
+        .filter(|field| field.field_type == "Json")
+        .map(|field| format!("'{}'", field.name))
+        .collect();
+
+    let filter_type = if has_entity && !json_fields.is_empty() {
+        format!(
+            "Partial<Omit<{}, {}>>",
+            entity_type,
+            json_fields.join(" | ")
+        )
+    } else {
+        input_type.clone()
+    };
+
+    let ctx = RepositoryContext {
+        model,
+        config,
+        class_name: class_name.clone(),
+        scope,
+        input_type,
+        filter_type,
+        return_type,
+        has_mapper,
+    };
+    let RepositoryContext {
+        input_type,
+        filter_type,
+        return_type,
+        ..
+    } = &ctx;
+
+    let methods = methods.unwrap_or_default();
+
+    for method in methods
+        .iter()
+        .filter(|method| is_supported(model, method, config))
+    {
+        match method {
+            RepositoryOperations::Create => write!(
+                abstract_repository,
+                "\n{}abstract create(data: {}): Promise<{}>",
+                style.indent(1),
+                input_type,
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::CreateMany => write!(
+                abstract_repository,
+                "\n{}abstract createMany(data: {}[]): Promise<{{ count: number }}>",
+                style.indent(1),
+                input_type
+            )
+            .unwrap(),
+            RepositoryOperations::Find => write!(
+                abstract_repository,
+                "\n{}abstract find(data: {}): Promise<{}>",
+                style.indent(1),
+                filter_type,
+                ctx.lookup_type()
+            )
+            .unwrap(),
+            RepositoryOperations::FindMany => write!(
+                abstract_repository,
+                "\n{}abstract findMany(data: {}{}): Promise<{}[]>",
+                style.indent(1),
+                filter_type,
+                ctx.find_many_parameters(),
+                ctx.find_many_type()
+            )
+            .unwrap(),
+            RepositoryOperations::FindAndCount => write!(
+                abstract_repository,
+                "\n{}abstract findAndCount(data: {}, pagination?: {{ skip?: number; take?: number }}): Promise<{{ items: {}[]; total: number }}>",
+                style.indent(1),
+                filter_type,
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::Update => write!(
+                abstract_repository,
+                "\n{}abstract update(id: {}, data: {}): Promise<{}>",
+                style.indent(1),
+                ctx.id_type(),
+                input_type,
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::Upsert => write!(
+                abstract_repository,
+                "\n{}abstract upsert(id: {}, data: {}): Promise<{}>",
+                style.indent(1),
+                ctx.id_type(),
+                input_type,
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::Delete => {
+                let names: &[&str] = if is_soft_delete(model) {
+                    &["softDelete", "hardDelete"]
+                } else {
+                    &["delete"]
+                };
+
+                for name in names {
+                    write!(
+                        abstract_repository,
+                        "\n{}abstract {}(id: {}): Promise<{}>",
+                        style.indent(1),
+                        name,
+                        ctx.id_type(),
+                        if config.delete_returns_entity {
+                            return_type
+                        } else {
+                            "void"
+                        }
+                    )
+                    .unwrap();
+                }
+            }
+            RepositoryOperations::DeleteMany => write!(
+                abstract_repository,
+                "\n{}abstract deleteMany(where: {}, options?: {{ allowEmptyWhere?: boolean }}): Promise<number>",
+                style.indent(1),
+                filter_type
+            )
+            .unwrap(),
+            RepositoryOperations::UpdateMany => write!(
+                abstract_repository,
+                "\n{}abstract updateMany(where: {}, data: {}, options?: {{ allowEmptyWhere?: boolean }}): Promise<number>",
+                style.indent(1),
+                filter_type,
+                input_type
+            )
+            .unwrap(),
+            RepositoryOperations::Search => write!(
+                abstract_repository,
+                "\n{}abstract search(query: string, pagination?: {{ skip?: number; take?: number }}): Promise<{}[]>",
+                style.indent(1),
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::Exists => write!(
+                abstract_repository,
+                "\n{}abstract exists(where: {}): Promise<boolean>",
+                style.indent(1),
+                filter_type
+            )
+            .unwrap(),
+            RepositoryOperations::FindByUnique => {
+                for field in unique_fields(model, config) {
+                    write!(
+                        abstract_repository,
+                        "\n{}abstract findBy{}({}: {}): Promise<{}>",
+                        style.indent(1),
+                        uppercase_first_char(&field.name),
+                        field.name,
+                        property_type(model, field, config).unwrap(),
+                        ctx.lookup_type()
+                    )
+                    .unwrap()
+                }
+            }
+            RepositoryOperations::Increment => write!(
+                abstract_repository,
+                "\n{}abstract increment<K extends {}>(id: {}, field: K, by: number): Promise<{}>",
+                style.indent(1),
+                numeric_field_union(model),
+                ctx.id_type(),
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::FindRandom => write!(
+                abstract_repository,
+                "\n{}abstract findRandom(): Promise<{}>",
+                style.indent(1),
+                ctx.nullable_type()
+            )
+            .unwrap(),
+            RepositoryOperations::FindManyStream => write!(
+                abstract_repository,
+                "\n{}abstract findManyStream(data: {}): AsyncGenerator<{}>",
+                style.indent(1),
+                filter_type,
+                return_type
+            )
+            .unwrap(),
+            RepositoryOperations::Aggregate => write!(
+                abstract_repository,
+                "\n{}abstract aggregate<K extends {}>(where: {}, options: {}): Promise<{}>",
+                style.indent(1),
+                numeric_field_union(model),
+                filter_type,
+                AGGREGATE_OPTIONS_TYPE,
+                AGGREGATE_RESULT_TYPE
+            )
+            .unwrap(),
+            RepositoryOperations::Count => write!(
+                abstract_repository,
+                "\n{}abstract count(data: {}): Promise<number>",
+                style.indent(1),
+                filter_type
+            )
+            .unwrap(),
+            RepositoryOperations::Restore => write!(
+                abstract_repository,
+                "\n{}abstract restore(id: {}): Promise<void>",
+                style.indent(1),
+                ctx.id_type()
+            )
+            .unwrap(),
+        }
+
+        // Unique finders and soft-deleted models' deletes are decorated one
+        // by one.
+        let decorator = if *method == RepositoryOperations::FindByUnique
+            || (*method == RepositoryOperations::Delete && is_soft_delete(model))
+        {
+            String::new()
+        } else {
+            ctx.cache_decorator(method, &method.to_string())
+        };
+
+        write!(
+            prisma_repository,
+            "\n{}{}{}",
+            style.indent(1),
+            decorator,
+            build_repository_methods(&ctx, method)
+        )
+        .unwrap();
+
+        if *method == RepositoryOperations::Find && config.find_by_id {
+            if let Some(id) = model
+                .fields
+                .iter()
+                .find(|field| field.name == "id" && field_type_name(field, config).is_some())
+            {
+                write!(
+                    abstract_repository,
+                    "\n{}abstract findById(id: {}): Promise<{}>",
+                    style.indent(1),
+                    property_type(model, id, config).unwrap(),
+                    ctx.lookup_type()
+                )
+                .unwrap();
+                write!(
+                    prisma_repository,
+                    "\n{}{}{}",
+                    style.indent(1),
+                    ctx.cache_decorator(method, "findById"),
+                    build_unique_finder(&ctx, id)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if let Some(retry) = &config.retry {
+        if methods
+            .iter()
+            .any(|method| is_read_operation(method) && is_supported(model, method, config))
+        {
+            write!(
+                prisma_repository,
+                "\n{}{}",
+                style.indent(1),
+                build_retry_helper(retry, style)
+            )
+            .unwrap();
+        }
+    }
+
+    if methods
+        .iter()
+        .any(|method| is_bulk_mutation(method) && is_supported(model, method, config))
+    {
+        write!(
+            prisma_repository,
+            "\n{}{}",
+            style.indent(1),
+            build_sanitize_where_helper(style)
+        )
+        .unwrap();
+    }
+
+    if let Some(cache) = &config.cache {
+        if methods.iter().any(|method| {
+            is_supported(model, method, config)
+                && (matches!(
+                    method,
+                    RepositoryOperations::Find | RepositoryOperations::FindByUnique
+                ) || is_mutation(method))
+        }) {
+            write!(
+                prisma_repository,
+                "\n{}{}",
+                style.indent(1),
+                build_cache_helpers(model, cache, style)
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(scope) = scope {
+        if !methods.is_empty() {
+            write!(
+                prisma_repository,
+                "\n{}{}",
+                style.indent(1),
+                build_scope_getter(scope, style)
+            )
+            .unwrap();
+        }
+    }
+
+    if matches!(config.prisma_access, PrismaAccess::Getter) && !methods.is_empty() {
+        write!(
+            prisma_repository,
+            "\n{}protected get client(): PrismaService {{\n{}return this.prisma\n{}}}",
+            style.indent(1),
+            style.indent(2),
+            style.indent(1)
+        )
+        .unwrap();
+    }
+
+    if config.preserve_custom_code {
+        for repository in [&mut abstract_repository, &mut prisma_repository] {
+            write!(
+                repository,
+                "\n{}{}\n{}{}",
+                style.indent(1),
+                CUSTOM_CODE_START,
+                style.indent(1),
+                CUSTOM_CODE_END
+            )
+            .unwrap();
+        }
+    }
+
+    write!(abstract_repository, "\n}}").unwrap();
+    write!(prisma_repository, "\n}}").unwrap();
+
+    if config.repository_factory {
+        // A plain PrismaClient stands in for the injected PrismaService.
+        let (factory_parameters, arguments): (Vec<String>, Vec<String>) = parameters
+            .iter()
+            .map(|(_, name, parameter_type)| match *name {
+                "prisma" => (
+                    "prisma: PrismaClient".to_string(),
+                    "prisma as PrismaService".to_string(),
+                ),
+                _ => (format!("{}: {}", name, parameter_type), name.to_string()),
+            })
+            .unzip();
+
+        write!(
+            prisma_repository,
+            "\n\nexport function create{}Repository({}): {}Repository {{\n{}return new Prisma{}Repository({})\n}}",
+            class_name,
+            factory_parameters.join(", "),
+            class_name,
+            style.indent(1),
+            class_name,
+            arguments.join(", ")
+        )
+        .unwrap();
+    }
+
+    if config.filter_helpers {
+        write!(
+            prisma_repository,
+            "\n\n{}",
+            build_filter_helpers(model, config)
+        )
+        .unwrap();
+    }
+
+    abstract_repository.push('\n');
+    prisma_repository.push('\n');
+
+    (abstract_repository, prisma_repository)
+}
+
+/// `{Model}Filters` object of typed `where` fragments: a `by{Field}` equality
+/// per scalar field and an after/before pair per date field.
+fn build_filter_helpers(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let where_input = format!("Prisma.{}WhereInput", model.name);
+    let mut helpers = format!(
+        "export const {}Filters = {{",
+        config.class_name(&model.name)
+    );
+
+    for field in model.fields.iter().filter(|field| !field.is_list) {
+        let parameter_type = match field.field_type.as_str() {
+            _ if field.is_enum() => format!("Prisma{}['{}']", model.name, field.name),
+            "String" => "string".to_string(),
+            "Int" | "Float" => "number".to_string(),
+            "Boolean" => "boolean".to_string(),
+            "DateTime" => {
+                // `createdAt` reads better as `createdAfter` than `createdAtAfter`.
+                let prefix = field.name.strip_suffix("At").unwrap_or(&field.name);
+
+                for (suffix, operator) in [("After", "gt"), ("Before", "lt")] {
+                    write!(
+                        helpers,
+                        "\n{}{}{}: (date: Date): {} => ({{ {}: {{ {}: date }} }}),",
+                        style.indent(1),
+                        prefix,
+                        suffix,
+                        where_input,
+                        field.name,
+                        operator
+                    )
+                    .unwrap();
+                }
+
+                continue;
+            }
+            _ => continue,
+        };
+
+        write!(
+            helpers,
+            "\n{}by{}: ({}: {}): {} => ({{ {} }}),",
+            style.indent(1),
+            uppercase_first_char(&field.name),
+            field.name,
+            parameter_type,
+            where_input,
+            field.name
+        )
+        .unwrap();
+    }
+
+    helpers.push_str("\n}");
+
+    helpers
+}
+
+fn create_mapper(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+    let mut mapper = build_enum_lookups(model, config);
+
+    let discriminator = config
+        .model(&model.name)
+        .and_then(|model_config| model_config.discriminator.as_ref())
+        .filter(|discriminator| {
+            model
+                .fields
+                .iter()
+                .any(|field| field.name == discriminator.field)
+        });
+
+    write!(
+        mapper,
+        "{} class {}Mapper {{\n{}static toDomain(data: Prisma{}): {} {{",
+        export_keyword(config),
+        class_name,
+        style.indent(1),
+        model.name,
+        class_name
+    )
+    .unwrap();
+
+    if discriminator.is_some() {
+        write!(mapper, "\n{}const props = {{", style.indent(2)).unwrap();
+    } else {
+        write!(mapper, "\n{}return new {}({{", style.indent(2), class_name).unwrap();
+    }
+
+    let transforms = config
+        .model(&model.name)
+        .map(|model_config| &model_config.transforms);
+
+    for field in &model.fields {
+        if get_field_with_type(model, field, false, config).is_some() {
+            let value = match transforms.and_then(|transforms| transforms.get(&field.name)) {
+                Some(transform) => transform.clone(),
+                None => match branded_id(model, config) {
+                    Some(id_type) if field.name == "id" => {
+                        format!("{} as {}", to_domain_value(field, config), id_type)
+                    }
+                    _ => to_domain_value(field, config),
+                },
+            };
+
+            write!(
+                mapper,
+                "\n{}{}: {},",
+                style.indent(3),
+                property_name(field, config),
+                value
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(discriminator) = discriminator {
+        write!(
+            mapper,
+            "\n{}}}\n\n{}switch (data.{}) {{",
+            style.indent(2),
+            style.indent(2),
+            discriminator.field
+        )
+        .unwrap();
+
+        for (value, class_name) in &discriminator.classes {
+            write!(
+                mapper,
+                "\n{}case '{}':\n{}return new {}(props)",
+                style.indent(3),
+                value,
+                style.indent(4),
+                class_name
+            )
+            .unwrap();
+        }
+
+        write!(
+            mapper,
+            "\n{}default:\n{}return new {}(props)\n{}}}\n{}}}",
+            style.indent(3),
+            style.indent(4),
+            class_name,
+            style.indent(2),
+            style.indent(1)
+        )
+        .unwrap();
+    } else {
+        write!(mapper, "\n{}}})\n{}}}", style.indent(2), style.indent(1)).unwrap();
+    }
+
+    write!(
+        mapper,
+        "\n\n{}static toPersistence(entity: {}): Prisma{} {{\n{}return {{",
+        style.indent(1),
+        class_name,
+        model.name,
+        style.indent(2)
+    )
+    .unwrap();
+
+    for field in &model.fields {
+        if get_field_with_type(model, field, false, config).is_some() {
+            write!(
+                mapper,
+                "\n{}{}: {},",
+                style.indent(3),
+                field.name,
+                to_persistence_value(field, config, false)
+            )
+            .unwrap();
+        }
+    }
+
+    // Repository inputs are partial entities, whose missing properties stay
+    // undefined so Prisma leaves them out of filters and updates.
+    write!(
+        mapper,
+        "\n{}}}\n{}}}\n\n{}static toPartialPersistence(entity: Partial<{}>): Partial<Prisma{}> {{\n{}return {{",
+        style.indent(2),
+        style.indent(1),
+        style.indent(1),
+        class_name,
+        model.name,
+        style.indent(2)
+    )
+    .unwrap();
+
+    for field in &model.fields {
+        if get_field_with_type(model, field, false, config).is_some() {
+            write!(
+                mapper,
+                "\n{}{}: {},",
+                style.indent(3),
+                field.name,
+                to_persistence_value(field, config, true)
+            )
+            .unwrap();
+        }
+    }
+
+    write!(mapper, "\n{}}}\n{}}}\n}}", style.indent(2), style.indent(1)).unwrap();
+
+    mapper
+}
+
+/// Reverses `to_domain_value`, converting an entity property back to the type
+/// Prisma Client expects. Properties of a `partial` entity may be undefined.
+fn to_persistence_value(field: &Field, config: &Config, partial: bool) -> String {
+    let value = format!("entity.{}", property_name(field, config));
+
+    // Prisma types absent values as `null`.
+    let passthrough = if !partial && field.is_optional && absent_value(config) != "null" {
+        format!("{} ?? null", value)
+    } else {
+        value.clone()
+    };
+
+    let convert = |value: &str| -> Option<String> {
+        if field.is_enum() {
+            return config
+                .translates_enum(&field.field_type)
+                .then(|| format!("{}ToPersistence[{}]", field.field_type, value));
+        }
+
+        match field.field_type.as_str() {
+            "Decimal" => Some(format!("new Prisma.Decimal({})", value)),
+            "BigInt" => Some(format!("BigInt({})", value)),
+            "DateTime" => match config.date_time_type {
+                DateTimeType::Date => None,
+                DateTimeType::Instant => Some(format!("new Date({}.epochMilliseconds)", value)),
+                DateTimeType::PlainDateTime => Some(format!(
+                    "new Date({}.toZonedDateTime('UTC').epochMilliseconds)",
+                    value
+                )),
+            },
+            _ => None,
+        }
+    };
+
+    let Some(converted) = (if field.is_list {
+        convert("value").map(|element| format!("{}.map((value) => {})", value, element))
+    } else {
+        convert(&value)
+    }) else {
+        return passthrough;
+    };
+
+    if partial {
+        return format!("{} != null ? {} : {}", value, converted, value);
+    }
+
+    if !field.is_optional {
+        return converted;
+    }
+
+    format!("{} != null ? {} : null", value, converted)
+}
+
+/// Lookups between the database and domain values of the model's translated
+/// enums, in both directions.
+fn build_enum_lookups(model: &Model, config: &Config) -> String {
+    let mut lookups = String::new();
+    let mut enum_types: Vec<&str> = Vec::new();
+
+    for field in model.fields.iter().filter(|field| field.is_enum()) {
+        if enum_types.contains(&field.field_type.as_str())
+            || !config.translates_enum(&field.field_type)
+        {
+            continue;
+        }
+
+        let pairs: Vec<(&str, String)> = field
+            .enum_values
+            .iter()
+            .map(|value| {
+                (
+                    value.as_str(),
+                    config.domain_enum_value(&field.field_type, value),
+                )
+            })
+            .collect();
+
+        for (direction, entries) in [
+            (
+                "ToDomain",
+                pairs
+                    .iter()
+                    .map(|(database, domain)| format!("{}: '{}'", database, domain))
+                    .collect::<Vec<String>>(),
+            ),
+            (
+                "ToPersistence",
+                pairs
+                    .iter()
+                    .map(|(database, domain)| format!("'{}': '{}'", domain, database))
+                    .collect::<Vec<String>>(),
+            ),
+        ] {
+            writeln!(
+                lookups,
+                "export const {}{} = {{ {} }} as const\n",
+                field.field_type,
+                direction,
+                entries.join(", ")
+            )
+            .unwrap();
+        }
+
+        enum_types.push(&field.field_type);
+    }
+
+    lookups
+}
+
+/// Expression converting a Prisma value to the entity's property type.
+fn to_domain_value(field: &Field, config: &Config) -> String {
+    let value = format!("data.{}", field.name);
+    let absent = absent_value(config);
+
+    // Prisma returns `null` for absent values, which only passes through
+    // unchanged when the domain layer uses `null` as well.
+    let passthrough = if field.is_optional && absent != "null" {
+        format!("{} ?? {}", value, absent)
+    } else {
+        value.clone()
+    };
+
+    if field.is_enum() && config.translates_enum(&field.field_type) {
+        let lookup = format!("{}ToDomain", field.field_type);
+
+        if field.is_list {
+            return format!("{}.map((value) => {}[value])", value, lookup);
+        }
+
+        if !field.is_optional {
+            return format!("{}[{}]", lookup, value);
+        }
+
+        return format!("{} != null ? {}[{}] : {}", value, lookup, value, absent);
+    }
+
+    if field.is_list {
+        let element = match (field.field_type.as_str(), &config.decimal_type) {
+            ("Decimal", DecimalType::String) => "String",
+            ("Decimal" | "BigInt", _) => "Number",
+            ("DateTime", _) => match config.date_time_type {
+                DateTimeType::Date => return passthrough,
+                DateTimeType::Instant => "(value) => Temporal.Instant.from(value.toISOString())",
+                DateTimeType::PlainDateTime => "(value) => Temporal.Instant.from(value.toISOString()).toZonedDateTimeISO('UTC').toPlainDateTime()",
+            },
+            _ => return passthrough,
+        };
+
+        let converted = format!("{}.map({})", value, element);
+
+        if !field.is_optional {
+            return converted;
+        }
+
+        return format!("{} != null ? {} : {}", value, converted, absent);
+    }
+
+    let converted = match (field.field_type.as_str(), &config.decimal_type) {
+        ("Decimal", DecimalType::String) => format!("{}.toString()", value),
+        ("Decimal" | "BigInt", _) => format!("Number({})", value),
+        ("Int" | "Float", _) => value.clone(),
+        ("DateTime", _) => match config.date_time_type {
+            DateTimeType::Date => return passthrough,
+            DateTimeType::Instant => format!("Temporal.Instant.from({}.toISOString())", value),
+            DateTimeType::PlainDateTime => format!(
+                "Temporal.Instant.from({}.toISOString()).toZonedDateTimeISO('UTC').toPlainDateTime()",
+                value
+            ),
+        },
+        _ => return passthrough,
+    };
+
+    if !field.is_optional {
+        return converted;
+    }
+
+    // Conversions would turn a missing value into `0`, `NaN` or throw, so
+    // optional fields keep the absent value explicitly instead.
+    if converted == value {
+        format!("{} ?? {}", value, absent)
+    } else {
+        format!("{} != null ? {} : {}", value, converted, absent)
+    }
+}
+
+fn create_entity(model: &Model, has_mapper: bool, config: &Config) -> String {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+    let entity_interface = String::from("I") + &class_name;
+    let mut entity = String::new();
+
+    let mut enum_types: Vec<&str> = Vec::new();
+
+    for field in model.fields.iter().filter(|field| field.is_enum()) {
+        if enum_types.contains(&field.field_type.as_str()) {
+            continue;
+        }
+
+        let values: Vec<String> = field
+            .enum_values
+            .iter()
+            .map(|value| format!("'{}'", config.domain_enum_value(&field.field_type, value)))
+            .collect();
+
+        writeln!(
+            entity,
+            "export type {} = {}\n",
+            field.field_type,
+            values.join(" | ")
+        )
+        .unwrap();
+
+        enum_types.push(&field.field_type);
+    }
+
+    if let Some(id_type) = branded_id(model, config) {
+        let id = model
+            .fields
+            .iter()
+            .find(|field| field.name == "id")
+            .unwrap();
+
+        writeln!(
+            entity,
+            "export type {} = {} & {{ readonly __brand: '{}' }}\n",
+            id_type,
+            field_type_name(id, config).unwrap(),
+            id_type
+        )
+        .unwrap();
+    }
+
+    write!(entity, "export interface {} {{", entity_interface).unwrap();
+
+    for field in &model.fields {
+        let parsed_field_option = get_field_with_type(model, field, false, config);
+
+        if let Some(parsed_field) = parsed_field_option {
+            entity.push_str(&parsed_field);
+        }
+    }
+
+    entity.push_str("\n}\n\n");
+
+    let mut implemented = entity_interface.clone();
+
+    if config.read_write_models {
+        write!(entity, "export interface {}ReadModel {{", class_name).unwrap();
+
+        for field in &model.fields {
+            if let Some(parsed_field) = get_field_with_type(model, field, true, config) {
+                entity.push_str(&parsed_field);
+            }
+        }
+
+        entity.push_str("\n}\n\n");
+
+        // Views can't be written to, so they only get a read model.
+        if !model.is_view {
+            write!(entity, "export interface {}WriteModel {{", class_name).unwrap();
+
+            // Optional fields can be left out of the input, which lets the
+            // generated DTOs implement the write model.
+            for field in &model.fields {
+                if MANAGED_FIELDS.contains(&field.name.as_str()) {
+                    continue;
+                }
+
+                if let Some(parsed_field) = get_field_with_type(model, field, false, config) {
+                    if field.is_optional {
+                        entity.push_str(&parsed_field.replacen(": ", "?: ", 1));
+                    } else {
+                        entity.push_str(&parsed_field);
+                    }
+                }
+            }
+
+            entity.push_str("\n}\n\n");
+        }
+
+        write!(implemented, ", {}ReadModel", class_name).unwrap();
+    }
+
+    if config.contracts_only {
+        entity.truncate(entity.trim_end().len());
+        entity.push('\n');
+
+        return entity;
+    }
+
+    write!(
+        entity,
+        "{} class {} implements {} {{",
+        export_keyword(config),
+        class_name,
+        implemented
+    )
+    .unwrap();
+
+    let props_style = matches!(config.entity_style, EntityStyle::Props);
+
+    if props_style {
+        write!(
+            entity,
+            "\n{}private readonly props: {}",
+            style.indent(1),
+            entity_interface
+        )
+        .unwrap();
+    } else {
+        for field in &model.fields {
+            let parsed_field_option = get_field_with_type(model, field, true, config);
+            if let Some(parsed_field) = parsed_field_option {
+                entity.push_str(&field_doc_comment(field, style));
+                entity.push_str(&parsed_field);
+            }
+        }
+    }
+
+    if config.static_schema {
+        write!(entity, "\n\n{}static readonly schema = [", style.indent(1)).unwrap();
+
+        for field in &model.fields {
+            if field_type_name(field, config).is_some() {
+                write!(
+                    entity,
+                    "\n{}{{ name: '{}', type: '{}{}', optional: {} }},",
+                    style.indent(2),
+                    property_name(field, config),
+                    field.field_type,
+                    if field.is_list { "[]" } else { "" },
+                    field.is_optional
+                )
+                .unwrap();
+            }
+        }
+
+        write!(entity, "\n{}] as const", style.indent(1)).unwrap();
+    }
+
+    let param_name = lowercase_first_char(&model.name);
+
+    let defaults: Vec<(&Field, String)> = model
+        .fields
+        .iter()
+        .filter(|field| field_type_name(field, config).is_some())
+        .filter_map(|field| default_value(field, config).map(|value| (field, value)))
+        .map(|(field, value)| match branded_id(model, config) {
+            Some(id_type) if field.name == "id" => (field, format!("{} as {}", value, id_type)),
+            _ => (field, value),
+        })
+        .collect();
+
+    let param_type = if defaults.is_empty() {
+        entity_interface.clone()
+    } else {
+        let defaulted_keys = defaults
+            .iter()
+            .map(|(field, _)| format!("'{}'", property_name(field, config)))
+            .collect::<Vec<String>>()
+            .join(" | ");
+
+        format!(
+            "Omit<{}, {}> & Partial<Pick<{}, {}>>",
+            entity_interface, defaulted_keys, entity_interface, defaulted_keys
+        )
+    };
+
+    write!(
+        entity,
+        "\n\n{}constructor({}: {}) {{",
+        style.indent(1),
+        param_name,
+        param_type
+    )
+    .unwrap();
+
+    if config.validate_construction {
+        let required: Vec<String> = model
+            .fields
+            .iter()
+            .filter(|field| field_type_name(field, config).is_some() && !field.is_optional)
+            .filter(|field| {
+                !defaults
+                    .iter()
+                    .any(|(defaulted, _)| defaulted.name == field.name)
+            })
+            .map(|field| format!("'{}'", property_name(field, config)))
+            .collect();
+
+        if !required.is_empty() {
+            write!(
+                entity,
+                "\n{}for (const key of [{}] as const) {{\n{}if ({}[key] === undefined) {{\n{}throw new Error(`{}.${{key}} is required`)\n{}}}\n{}}}\n",
+                style.indent(2),
+                required.join(", "),
+                style.indent(3),
+                param_name,
+                style.indent(4),
+                class_name,
+                style.indent(3),
+                style.indent(2)
+            )
+            .unwrap();
+        }
+    }
+
+    if props_style {
+        write!(
+            entity,
+            "\n{}this.props = {{\n{}...{},",
+            style.indent(2),
+            style.indent(3),
+            param_name
+        )
+        .unwrap();
+
+        for (field, value) in &defaults {
+            write!(
+                entity,
+                "\n{}{}: {}.{} ?? {},",
+                style.indent(3),
+                property_name(field, config),
+                param_name,
+                property_name(field, config),
+                value
+            )
+            .unwrap();
+        }
+
+        write!(entity, "\n{}}}", style.indent(2)).unwrap();
+    } else {
+        write!(
+            entity,
+            "\n{}Object.assign(this, {})",
+            style.indent(2),
+            param_name
+        )
+        .unwrap();
+
+        for (field, value) in &defaults {
+            write!(
+                entity,
+                "\n{}this.{} = {}.{} ?? {}",
+                style.indent(2),
+                property_name(field, config),
+                param_name,
+                property_name(field, config),
+                value
+            )
+            .unwrap();
+        }
+    }
+
+    write!(entity, "\n{}}}", style.indent(1)).unwrap();
+
+    if props_style {
+        for field in &model.fields {
+            if let Some(field_type) = property_type(model, field, config) {
+                write!(
+                    entity,
+                    "\n{}\n{}get {}(): {} {{\n{}return this.props.{}\n{}}}",
+                    field_doc_comment(field, style),
+                    style.indent(1),
+                    property_name(field, config),
+                    type_annotation(&field_type, field.is_list, field.is_optional, config),
+                    style.indent(2),
+                    property_name(field, config),
+                    style.indent(1)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let sensitive = sensitive_fields(model, config);
+    let source = if props_style { "this.props" } else { "this" };
+
+    if !sensitive.is_empty() {
+        let names: Vec<String> = sensitive
+            .iter()
+            .map(|field| property_name(field, config))
+            .collect();
+
+        write!(
+            entity,
+            "\n\n{}toJSON(): Omit<{}, {}> {{\n{}const {{ {}, ...rest }} = {}\n{}return rest\n{}}}",
+            style.indent(1),
+            entity_interface,
+            names
+                .iter()
+                .map(|name| format!("'{}'", name))
+                .collect::<Vec<String>>()
+                .join(" | "),
+            style.indent(2),
+            names.join(", "),
+            source,
+            style.indent(2),
+            style.indent(1)
+        )
+        .unwrap();
+    } else if props_style {
+        write!(
+            entity,
+            "\n\n{}toJSON(): {} {{\n{}return {{ ...this.props }}\n{}}}",
+            style.indent(1),
+            entity_interface,
+            style.indent(2),
+            style.indent(1)
+        )
+        .unwrap();
+    }
+
+    let ownership_field = config
+        .model(&model.name)
+        .and_then(|model_config| model_config.ownership_field.as_ref())
+        .and_then(|name| model.fields.iter().find(|field| &field.name == name));
+
+    if let Some(field) = ownership_field {
+        if let Some(field_type) = field_type_name(field, config) {
+            write!(
+                entity,
+                "\n\n{}static isOwnedBy(entity: {}, {}: {}): boolean {{\n{}return entity.{} === {}\n{}}}",
+                style.indent(1),
+                class_name,
+                field.name,
+                field_type,
+                style.indent(2),
+                property_name(field, config),
+                field.name,
+                style.indent(1)
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(equality) = &config.equality {
+        if let Some(equals) = build_equals(model, &class_name, equality, config) {
+            write!(entity, "\n\n{}{}", style.indent(1), equals).unwrap();
+        }
+    }
+
+    if has_mapper {
+        write!(
+            entity,
+            "\n\n{}refresh(data: Prisma{}): {} {{\n{}return {}Mapper.toDomain(data)\n{}}}",
+            style.indent(1),
+            model.name,
+            class_name,
+            style.indent(2),
+            class_name,
+            style.indent(1)
+        )
+        .unwrap();
+    }
+
+    writeln!(entity, "\n}}").unwrap();
+
+    if config.type_guards {
+        entity.push('\n');
+        entity.push_str(&create_type_guard(model, config));
+    }
+
+    if config.entity_builder {
+        entity.push('\n');
+        entity.push_str(&create_entity_builder(model, config));
+    }
+
+    entity
+}
+
+/// `is{Model}` guard narrowing untyped data to the entity interface, checking
+/// the runtime type of every field.
+fn create_type_guard(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+
+    let checks: Vec<String> = model
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let field_type = field_type_name(field, config)?;
+            let check = |value: &str| match field_type {
+                "string" | "number" | "boolean" => format!("typeof {} === '{}'", value, field_type),
+                "Date"
+                | "Temporal.Instant"
+                | "Temporal.PlainDateTime"
+                | "Buffer"
+                | "Uint8Array" => {
+                    format!("{} instanceof {}", value, field_type)
+                }
+                // Enums are string unions.
+                _ => format!("typeof {} === 'string'", value),
+            };
+
+            let property = format!("record.{}", property_name(field, config));
+
+            // Any JSON value passes, `null` included.
+            if field.field_type == "Json" {
+                return Some(format!("{} !== undefined", property));
+            }
+
+            let check = if field.is_list {
+                format!(
+                    "Array.isArray({}) && {}.every((item) => {})",
+                    property,
+                    property,
+                    check("item")
+                )
+            } else {
+                check(&property)
+            };
+
+            Some(if field.is_optional {
+                format!("({} == null || {})", property, check)
+            } else {
+                check
+            })
+        })
+        .collect();
+
+    format!(
+        "export function is{}(value: unknown): value is I{} {{\n{}if (typeof value !== 'object' || value === null) {{\n{}return false\n{}}}\n\n{}const record = value as Record<string, unknown>\n\n{}return (\n{}{}\n{})\n}}\n",
+        class_name,
+        class_name,
+        style.indent(1),
+        style.indent(2),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(2),
+        checks.join(&format!(" &&\n{}", style.indent(2))),
+        style.indent(1)
+    )
+}
+
+/// Fluent builder collecting the entity's fields before constructing it.
+/// Fields left unset fall back to the constructor's defaults.
+fn create_entity_builder(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+    let mut builder = String::new();
+
+    write!(
+        builder,
+        "export class {}Builder {{\n{}private readonly props: Partial<I{}> = {{}}",
+        class_name,
+        style.indent(1),
+        class_name
+    )
+    .unwrap();
+
+    for field in &model.fields {
+        if let Some(field_type) = property_type(model, field, config) {
+            let name = property_name(field, config);
+
+            write!(
+                builder,
+                "\n\n{}set{}({}: {}): this {{\n{}this.props.{} = {}\n{}return this\n{}}}",
+                style.indent(1),
+                uppercase_first_char(&name),
+                name,
+                type_annotation(&field_type, field.is_list, field.is_optional, config),
+                style.indent(2),
+                name,
+                name,
+                style.indent(2),
+                style.indent(1)
+            )
+            .unwrap();
+        }
+    }
+
+    write!(
+        builder,
+        "\n\n{}build(): {} {{\n{}return new {}(this.props as ConstructorParameters<typeof {}>[0])\n{}}}\n}}\n",
+        style.indent(1),
+        class_name,
+        style.indent(2),
+        class_name,
+        class_name,
+        style.indent(1)
+    )
+    .unwrap();
+
+    builder
+}
+
+/// `equals` comparing two entities by id, or by every field for value
+/// objects without an identity.
+fn build_equals(
+    model: &Model,
+    class_name: &str,
+    equality: &Equality,
+    config: &Config,
+) -> Option<String> {
+    let style = config.indent_style;
+    let comparisons: Vec<String> = match equality {
+        Equality::Id => {
+            let id = model.fields.iter().find(|field| field.name == "id")?;
+
+            vec![format!("this.{} === other.{}", id.name, id.name)]
+        }
+        Equality::Structural => model
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let field_type = field_type_name(field, config)?;
+                let name = property_name(field, config);
+                let access = if field.is_optional { "?." } else { "." };
+
+                // Dates, arrays, JSON values and bytes are compared by value
+                // rather than by reference.
+                Some(
+                    if field.is_list || matches!(field.field_type.as_str(), "Json" | "Bytes") {
+                        format!(
+                            "JSON.stringify(this.{}) === JSON.stringify(other.{})",
+                            name, name
+                        )
+                    } else if field_type == "Date" {
+                        format!(
+                            "this.{}{}getTime() === other.{}{}getTime()",
+                            name, access, name, access
+                        )
+                    } else if field_type.starts_with("Temporal.") {
+                        format!(
+                            "this.{}{}toString() === other.{}{}toString()",
+                            name, access, name, access
+                        )
+                    } else {
+                        format!("this.{} === other.{}", name, name)
+                    },
+                )
+            })
+            .collect(),
+    };
+
+    if comparisons.is_empty() {
+        return None;
+    }
+
+    if let [comparison] = comparisons.as_slice() {
+        return Some(format!(
+            "equals(other: {}): boolean {{\n{}return {}\n{}}}",
+            class_name,
+            style.indent(2),
+            comparison,
+            style.indent(1)
+        ));
+    }
+
+    Some(format!(
+        "equals(other: {}): boolean {{\n{}return (\n{}{}\n{})\n{}}}",
+        class_name,
+        style.indent(2),
+        style.indent(3),
+        comparisons.join(&format!(" &&\n{}", style.indent(3))),
+        style.indent(2),
+        style.indent(1)
+    ))
+}
+
+/// JSDoc block for a field's `/// @example` schema comment.
+fn field_doc_comment(field: &Field, style: IndentStyle) -> String {
+    match field.tag_value("@example") {
+        Some(example) if !example.is_empty() => {
+            format!("\n{}/** @example {} */", style.indent(1), example)
+        }
+        _ => String::new(),
+    }
+}
+
+/// TypeScript expression for a field's `@default`/`@updatedAt` value, when it
+/// can be computed outside of the database.
+fn default_value(field: &Field, config: &Config) -> Option<String> {
+    let now = match config.date_time_type {
+        DateTimeType::Date => "new Date()",
+        DateTimeType::Instant => "Temporal.Now.instant()",
+        DateTimeType::PlainDateTime => "Temporal.Now.plainDateTimeISO()",
+    };
+
+    if field.attribute("updatedAt").is_some() {
+        return Some(now.to_string());
+    }
+
+    let arguments = field.attribute("default")?.arguments.as_deref()?.trim();
+
+    if field.is_list {
+        return None;
+    }
+
+    if field.enum_values.iter().any(|value| value == arguments) {
+        return Some(format!(
+            "'{}'",
+            config.domain_enum_value(&field.field_type, arguments)
+        ));
+    }
+
+    match (field.field_type.as_str(), arguments) {
+        ("DateTime", "now()") => Some(now.to_string()),
+        ("String", "uuid()") => Some("crypto.randomUUID()".to_string()),
+        ("Boolean", "true" | "false") => Some(arguments.to_string()),
+        ("Int" | "Float" | "Decimal" | "BigInt", value) if value.parse::<f64>().is_ok() => {
+            Some(value.to_string())
+        }
+        ("String", value) if value.starts_with('"') && value.ends_with('"') && value.len() > 1 => {
+            Some(format!("'{}'", &value[1..value.len() - 1]))
+        }
+        _ => None,
+    }
+}
+
+fn create_prisma_service(config: &Config) -> String {
+    let style = config.indent_style;
+
+    if let DecoratorProfile::Plain = config.decorators {
+        return format!(
+            r#"import {{ PrismaClient }} from '@prisma/client'
+
+{} class PrismaService extends PrismaClient {{}}
+"#,
+            export_keyword(config)
+        );
+    }
+
+    format!(
+        r#"import {{ Injectable, OnModuleInit }} from '@nestjs/common'
+import {{ PrismaClient }} from '@prisma/client'
+
+@Injectable()
+{} class PrismaService extends PrismaClient implements OnModuleInit {{
+{}async onModuleInit() {{
+{}await this.$connect()
+{}}}
+}}
+"#,
+        export_keyword(config),
+        style.indent(1),
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+/// Query-string DTO for list endpoints: pagination, sorting and an optional
+/// filter per scalar field.
+fn create_list_query_dto(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let sensitive = sensitive_fields(model, config);
+    let is_sensitive = |field: &Field| sensitive.iter().any(|other| other.name == field.name);
+
+    let sortable: Vec<String> = model
+        .fields
+        .iter()
+        .filter(|field| {
+            field_type_name(field, config).is_some()
+                && !matches!(field.field_type.as_str(), "Json" | "Bytes")
+                && !field.is_list
+                && !is_sensitive(field)
+        })
+        .map(|field| format!("'{}'", field.name))
+        .collect();
+
+    let mut transformer_imports = vec!["Type"];
+    let mut validator_imports = vec!["IsIn", "IsInt", "IsOptional", "Min"];
+    let mut filters = String::new();
+
+    for field in &model.fields {
+        // Filters are declared under the query parameter's own name, which
+        // can't be a reserved class member.
+        if is_sensitive(field)
+            || field.is_list
+            || RESERVED_PROPERTY_NAMES.contains(&field.name.as_str())
+        {
+            continue;
+        }
+
+        // Query strings only carry text, so numbers and booleans are coerced
+        // before they are validated.
+        let (decorators, field_type) = match field.field_type.as_str() {
+            "String" => (vec!["@IsString()"], "string"),
+            "Int" => (vec!["@Type(() => Number)", "@IsInt()"], "number"),
+            "Float" => (vec!["@Type(() => Number)", "@IsNumber()"], "number"),
+            "Boolean" => (
+                vec![
+                    "@Transform(({ value }) => value === 'true')",
+                    "@IsBoolean()",
+                ],
+                "boolean",
+            ),
+            _ => continue,
+        };
+
+        for decorator in &decorators {
+            let name = &decorator[1..decorator.find('(').unwrap()];
+            let imports = if matches!(name, "Type" | "Transform") {
+                &mut transformer_imports
+            } else {
+                &mut validator_imports
+            };
+
+            if !imports.contains(&name) {
+                imports.push(name);
+            }
+        }
+
+        write!(
+            filters,
+            "\n\n{}@IsOptional()\n{}{}\n{}{}?: {}",
+            style.indent(1),
+            style.indent(1),
+            decorators.join(&format!("\n{}", style.indent(1))),
+            style.indent(1),
+            field.name,
+            field_type
+        )
+        .unwrap();
+    }
+
+    transformer_imports.sort();
+    validator_imports.sort();
+
+    format!(
+        r#"import {{ {} }} from 'class-transformer'
+import {{ {} }} from 'class-validator'
+
+{} class List{}QueryDto {{
+{}@IsOptional()
+{}@Type(() => Number)
+{}@IsInt()
+{}@Min(1)
+{}page?: number
+
+{}@IsOptional()
+{}@Type(() => Number)
+{}@IsInt()
+{}@Min(1)
+{}pageSize?: number
+
+{}@IsOptional()
+{}@IsIn([{}])
+{}sortBy?: {}
+
+{}@IsOptional()
+{}@IsIn(['asc', 'desc'])
+{}order?: 'asc' | 'desc'{}
+}}
+"#,
+        transformer_imports.join(", "),
+        validator_imports.join(", "),
+        export_keyword(config),
+        model.name,
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        sortable.join(", "),
+        style.indent(1),
+        sortable.join(" | "),
+        style.indent(1),
+        style.indent(1),
+        style.indent(1),
+        filters
+    )
+}
+
+/// Input DTOs for creating and updating a model. Managed fields are set by
+/// the database or the entity, so they aren't accepted as input.
+fn create_dto(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let fields: Vec<(&Field, &str)> = model
+        .fields
+        .iter()
+        // class-validator has nothing to check arbitrary JSON or binary data
+        // against.
+        .filter(|field| {
+            !MANAGED_FIELDS.contains(&field.name.as_str())
+                && !matches!(field.field_type.as_str(), "Json" | "Bytes")
+        })
+        .filter_map(|field| field_type_name(field, config).map(|field_type| (field, field_type)))
+        .collect();
+
+    // The DTOs implement the entity's write model, less the fields they can't
+    // validate.
+    let write_model = (config.read_write_models && !model.is_view).then(|| {
+        let write_model = format!("{}WriteModel", config.class_name(&model.name));
+        let unvalidated: Vec<String> = model
+            .fields
+            .iter()
+            .filter(|field| {
+                !MANAGED_FIELDS.contains(&field.name.as_str())
+                    && matches!(field.field_type.as_str(), "Json" | "Bytes")
+            })
+            .map(|field| format!("'{}'", property_name(field, config)))
+            .collect();
+
+        if unvalidated.is_empty() {
+            write_model
+        } else {
+            format!("Omit<{}, {}>", write_model, unvalidated.join(" | "))
+        }
+    });
+
+    let mut validator_imports: Vec<String> = Vec::new();
+    let mut enum_imports: Vec<&str> = Vec::new();
+    let mut has_date_transform = false;
+    let mut classes = String::new();
+
+    for (name, all_optional) in [("Create", false), ("Update", true)] {
+        if !classes.is_empty() {
+            classes.push('\n');
+        }
+
+        write!(
+            classes,
+            "{} class {}{}Dto",
+            export_keyword(config),
+            name,
+            model.name
+        )
+        .unwrap();
+
+        match (&write_model, all_optional) {
+            (Some(write_model), false) => write!(classes, " implements {}", write_model).unwrap(),
+            (Some(write_model), true) => {
+                write!(classes, " implements Partial<{}>", write_model).unwrap()
+            }
+            (None, _) => {}
+        }
+
+        classes.push_str(" {");
+
+        for (index, (field, field_type)) in fields.iter().enumerate() {
+            let is_optional = all_optional || field.is_optional;
+            let mut decorators = Vec::new();
+
+            if is_optional {
+                // `@IsOptional` lets `null` through as well.
+                decorators.push(match config.dto_nullability {
+                    Some(DtoNullability::Optional) => {
+                        "@ValidateIf((_, value) => value !== undefined)".to_string()
+                    }
+                    _ => "@IsOptional()".to_string(),
+                });
+            }
+
+            if field.is_list {
+                decorators.push("@IsArray()".to_string());
+            }
+
+            if let Some(decorator) = validator_decorator(field, field_type) {
+                decorators.push(decorator);
+            }
+
+            for decorator in &decorators {
+                let name = decorator[1..decorator.find('(').unwrap()].to_string();
+
+                if !validator_imports.contains(&name) {
+                    validator_imports.push(name);
+                }
+            }
+
+            if let Some(transform) = date_transform(field, field_type, is_optional) {
+                decorators.insert(0, transform);
+                has_date_transform = true;
+            }
+
+            if field.is_enum() && !enum_imports.contains(&field.field_type.as_str()) {
+                enum_imports.push(&field.field_type);
+            }
+
+            if index > 0 && !decorators.is_empty() {
+                classes.push('\n');
+            }
+
+            for decorator in &decorators {
+                write!(classes, "\n{}{}", style.indent(1), decorator).unwrap();
+            }
+
+            write!(
+                classes,
+                "\n{}{}{}: {}",
+                style.indent(1),
+                property_name(field, config),
+                if is_optional { "?" } else { "" },
+                dto_type_annotation(field, field_type, config)
+            )
+            .unwrap();
+        }
+
+        classes.push_str("\n}\n");
+    }
+
+    let mut dto = String::new();
+
+    // Prisma Client exports the schema enums as runtime objects that
+    // `@IsEnum` can check against.
+    if !enum_imports.is_empty() {
+        writeln!(
+            dto,
+            "import {{ {} }} from '@prisma/client'",
+            enum_imports.join(", ")
+        )
+        .unwrap();
+    }
+
+    if has_date_transform {
+        writeln!(dto, "import {{ Transform }} from 'class-transformer'").unwrap();
+    }
+
+    if write_model.is_some() {
+        let class_name = config.class_name(&model.name);
+
+        writeln!(
+            dto,
+            "import {{ {}WriteModel }} from '{}'",
+            class_name,
+            relative_specifier(ModuleType::Dto, ModuleType::Entity, &class_name, config)
+        )
+        .unwrap();
+    }
+
+    if !validator_imports.is_empty() {
+        validator_imports.sort();
+
+        writeln!(
+            dto,
+            "import {{ {} }} from 'class-validator'",
+            validator_imports.join(", ")
+        )
+        .unwrap();
+    }
+
+    if !dto.is_empty() {
+        dto.push('\n');
+    }
+
+    dto.push_str(&classes);
+
+    dto
+}
+
+/// class-transformer decorator converting the ISO strings received over HTTP
+/// into the date type of a DTO field.
+fn date_transform(field: &Field, field_type: &str, is_optional: bool) -> Option<String> {
+    let convert = |value: &str| match field_type {
+        "Date" => Some(format!("new Date({})", value)),
+        "Temporal.Instant" | "Temporal.PlainDateTime" => {
+            Some(format!("{}.from({})", field_type, value))
+        }
+        _ => None,
+    };
+
+    let converted = if field.is_list {
+        format!("value.map((item: string) => {})", convert("item")?)
+    } else {
+        convert("value")?
+    };
+
+    if is_optional {
+        return Some(format!(
+            "@Transform(({{ value }}) => (value == null ? value : {}))",
+            converted
+        ));
+    }
+
+    Some(format!("@Transform(({{ value }}) => {})", converted))
+}
+
+/// class-validator decorator checking a DTO field against its mapped type.
+/// Lists validate each of their elements.
+fn validator_decorator(field: &Field, field_type: &str) -> Option<String> {
+    let each = if field.is_list { "{ each: true }" } else { "" };
+
+    if field.is_enum() {
+        let options = if field.is_list {
+            ", { each: true }"
+        } else {
+            ""
+        };
+
+        return Some(format!("@IsEnum({}{})", field.field_type, options));
+    }
+
+    let name = match field_type {
+        "string" => "IsString",
+        "number" => "IsNumber",
+        "boolean" => "IsBoolean",
+        "Date" => "IsDate",
+        _ => return None,
+    };
+
+    // `@IsNumber` takes its own options object before the validation options.
+    if name == "IsNumber" && field.is_list {
+        return Some(format!("@IsNumber({{}}, {})", each));
+    }
+
+    Some(format!("@{}({})", name, each))
+}
+
+/// Properties of a JSON Schema, serialized in the model's field order.
+struct SchemaProperties(Vec<(String, Value)>);
+
+impl Serialize for SchemaProperties {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+
+        for (name, schema) in &self.0 {
+            map.serialize_entry(name, schema)?;
+        }
+
+        map.end()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSchema {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    title: String,
+    #[serde(rename = "type")]
+    schema_type: &'static str,
+    properties: SchemaProperties,
+    required: Vec<String>,
+    additional_properties: bool,
+}
+
+/// JSON Schema of the entity's serialized form, for validators and
+/// consumers outside of TypeScript.
+fn create_json_schema(model: &Model, config: &Config) -> String {
+    let sensitive = sensitive_fields(model, config);
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+
+    for field in &model.fields {
+        // Sensitive fields are left out of the entity's `toJSON`.
+        if sensitive.iter().any(|other| other.name == field.name) {
+            continue;
+        }
+
+        let Some(mut schema) = json_schema_type(field, config) else {
+            continue;
+        };
+
+        if field.is_list {
+            schema = json!({ "type": "array", "items": schema });
+        }
+
+        let name = property_name(field, config);
+
+        // `null` survives serialization, so null absent values keep the
+        // property required but nullable. `undefined` ones drop it.
+        match (field.is_optional, &config.absent_value) {
+            (true, AbsentValue::Undefined) => {}
+            (true, AbsentValue::Null) => {
+                schema = json!({ "anyOf": [schema, { "type": "null" }] });
+                required.push(name.clone());
+            }
+            (false, _) => required.push(name.clone()),
+        }
+
+        properties.push((name, schema));
+    }
+
+    let schema = JsonSchema {
+        schema: "https://json-schema.org/draft/2020-12/schema",
+        title: config.class_name(&model.name),
+        schema_type: "object",
+        properties: SchemaProperties(properties),
+        required,
+        additional_properties: false,
+    };
+
+    let mut contents = serde_json::to_string_pretty(&schema).unwrap();
+    contents.push('\n');
+
+    contents
+}
+
+/// Names of the model's fields, table, Prisma delegate and repository DI
+/// token, for code that would otherwise spell them out as strings.
+fn create_constants(model: &Model, config: &Config) -> String {
+    let class_name = config.class_name(&model.name);
+    let prefix = to_kebab_case(&class_name).replace('-', "_").to_uppercase();
+
+    let fields: Vec<String> = model
+        .fields
+        .iter()
+        .filter(|field| field_type_name(field, config).is_some())
+        .map(|field| format!("'{}'", field.name))
+        .collect();
+
+    format!(
+        "export const {prefix}_FIELDS = [{}] as const\n\nexport type {}Field = (typeof {prefix}_FIELDS)[number]\n\nexport const {prefix}_TABLE = '{}'\n\nexport const {prefix}_DELEGATE = '{}'\n\nexport const {prefix}_REPOSITORY = Symbol('{}Repository')\n",
+        fields.join(", "),
+        class_name,
+        model.table_name(),
+        lowercase_first_char(&model.name),
+        class_name,
+    )
+}
+
+/// Payload interfaces of the events the Prisma repository emits after its
+/// mutations.
+fn create_events(
+    model: &Model,
+    methods: Option<&[RepositoryOperations]>,
+    has_entity: bool,
+    config: &Config,
+) -> String {
+    let class_name = config.class_name(&model.name);
+    let payload_type = match (has_entity, config.contracts_only) {
+        (false, _) => "any".to_string(),
+        (true, true) => format!("I{}", class_name),
+        (true, false) => class_name.clone(),
+    };
+
+    let mut events: Vec<&str> = Vec::new();
+
+    for op in methods
+        .unwrap_or_default()
+        .iter()
+        .filter(|op| is_supported(model, op, config))
+    {
+        if let Some(event) = mutation_event(op) {
+            if !events.contains(&event) {
+                events.push(event);
+            }
+        }
+    }
+
+    events
+        .into_iter()
+        .map(|event| {
+            let payload = match event {
+                "Deleted" | "Restored" => format!("id: {}", id_type(model, config)),
+                _ => format!("{}: {}", lowercase_first_char(&class_name), payload_type),
+            };
+
+            format!(
+                "export interface {}{}Event {{\n{}{}\n}}\n",
+                class_name,
+                event,
+                config.indent_style.indent(1),
+                payload
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Jest spec running the Prisma repository against the database in
+/// `TEST_DATABASE_URL`, emptying the table before each test.
+fn create_integration_spec(
+    model: &Model,
+    methods: Option<&[RepositoryOperations]>,
+    config: &Config,
+) -> String {
+    let style = config.indent_style;
+    let class_name = config.class_name(&model.name);
+    let has = |operation: RepositoryOperations| {
+        methods.map_or(
+            matches!(
+                operation,
+                RepositoryOperations::Create | RepositoryOperations::Find
+            ),
+            |methods| methods.contains(&operation),
+        ) && is_supported(model, &operation, config)
+    };
+
+    let data: String = model
+        .fields
+        .iter()
+        .filter(|field| {
+            !MANAGED_FIELDS.contains(&field.name.as_str())
+                && !field.is_optional
+                && field.attribute("default").is_none()
+                && field.attribute("updatedAt").is_none()
+        })
+        .filter_map(|field| {
+            sample_value(field, config).map(|value| {
+                format!(
+                    "\n{}{}: {},",
+                    style.indent(2),
+                    property_name(field, config),
+                    value
+                )
+            })
+        })
+        .collect();
+
+    let mut arguments = vec!["prisma".to_string()];
+
+    if let Some(scope) = base_scope(model, config) {
+        let context: Vec<String> = scope
+            .fields
+            .iter()
+            .filter_map(|name| model.fields.iter().find(|field| &field.name == name))
+            .filter_map(|field| {
+                sample_value(field, config).map(|value| format!("{}: {}", field.name, value))
+            })
+            .collect();
+
+        arguments.push(format!("{{ {} }} as never", context.join(", ")));
+    }
+
+    if config.cache.is_some() {
+        arguments.push(
+            "{ get: async () => undefined, set: async () => {}, del: async () => {} } as never"
+                .to_string(),
+        );
+    }
+
+    if config.events.is_some() {
+        arguments.push("{ emit: () => true } as never".to_string());
+    }
+
+    let mut spec = format!(
+        r#"describe('Prisma{}Repository (integration)', () => {{
+{}const prisma = new PrismaService({{ datasourceUrl: process.env.TEST_DATABASE_URL }})
+{}const repository = new Prisma{}Repository({})
+{}const data = {{{}
+{}}}
+
+{}beforeEach(async () => {{
+{}await prisma.{}.deleteMany()
+{}}})
+
+{}afterAll(async () => {{
+{}await prisma.$disconnect()
+{}}})"#,
+        class_name,
+        style.indent(1),
+        style.indent(1),
+        class_name,
+        arguments.join(", "),
+        style.indent(1),
+        data,
+        style.indent(1),
+        style.indent(1),
+        style.indent(2),
+        lowercase_first_char(&model.name),
+        style.indent(1),
+        style.indent(1),
+        style.indent(2),
+        style.indent(1)
+    );
+
+    if has(RepositoryOperations::Create) && has(RepositoryOperations::Find) {
+        // Looked up by primary key when the repository has a findById.
+        let find = if config.find_by_id {
+            "repository.findById(created.id)"
+        } else {
+            "repository.find({ id: created.id })"
+        };
+
+        write!(
+            spec,
+            r#"
+
+{}it('finds a created record', async () => {{
+{}const created = await repository.create(data)
+
+{}const found = await {}
+
+{}expect(found?.id).toEqual(created.id)
+{}}})"#,
+            style.indent(1),
+            style.indent(2),
+            style.indent(2),
+            find,
+            style.indent(2),
+            style.indent(1)
+        )
+        .unwrap();
+
+        if has(RepositoryOperations::Delete) {
+            write!(
+                spec,
+                r#"
+
+{}it('no longer finds a deleted record', async () => {{
+{}const created = await repository.create(data)
+
+{}await repository.{}(created.id)
+
+{}expect(await {}).toBeNull()
+{}}})"#,
+                style.indent(1),
+                style.indent(2),
+                style.indent(2),
+                if is_soft_delete(model) {
+                    "softDelete"
+                } else {
+                    "delete"
+                },
+                style.indent(2),
+                find,
+                style.indent(1)
+            )
+            .unwrap();
+        }
+    }
+
+    spec.push_str("\n})\n");
+
+    spec
+}
+
+/// Placeholder value for a required field in generated specs.
+fn sample_value(field: &Field, config: &Config) -> Option<String> {
+    if field.is_list {
+        return field_type_name(field, config).map(|_| "[]".to_string());
+    }
+
+    if field.is_enum() {
+        return field
+            .enum_values
+            .first()
+            .map(|value| format!("'{}'", config.domain_enum_value(&field.field_type, value)));
+    }
+
+    Some(match (field.field_type.as_str(), &config.decimal_type) {
+        ("String", _) => format!("'{}'", field.name),
+        ("Decimal", DecimalType::String) => "'1'".to_string(),
+        ("Int" | "Float" | "Decimal" | "BigInt", _) => "1".to_string(),
+        ("Boolean", _) => "true".to_string(),
+        ("Json", _) => "{}".to_string(),
+        ("Bytes", _) => match config.bytes_type {
+            BytesType::Buffer => "Buffer.from([])",
+            BytesType::Uint8Array => "new Uint8Array()",
+        }
+        .to_string(),
+        ("DateTime", _) => match config.date_time_type {
+            DateTimeType::Date => "new Date()",
+            DateTimeType::Instant => "Temporal.Now.instant()",
+            DateTimeType::PlainDateTime => "Temporal.Now.plainDateTimeISO()",
+        }
+        .to_string(),
+        _ => return None,
+    })
+}
+
+/// MikroORM entity class for the model, mapped to the same table and
+/// columns as the Prisma model.
+fn create_mikro_orm_entity(model: &Model, config: &Config) -> String {
+    let style = config.indent_style;
+    let mut decorators = vec!["Entity"];
+    let mut properties = Vec::new();
+
+    for field in &model.fields {
+        let Some((column_type, property_type)) = mikro_orm_type(field) else {
+            continue;
+        };
+        let column_type = if field.is_list { "array" } else { column_type };
+        let name = property_name(field, config);
+        let mut options = Vec::new();
+
+        if !field.is_enum() {
+            options.push(format!("type: '{}'", column_type));
+        }
+
+        if name != field.column_name() {
+            options.push(format!("fieldName: '{}'", field.column_name()));
+        }
+
+        if field.is_optional {
+            options.push("nullable: true".to_string());
+        }
+
+        let default = field
+            .attribute("default")
+            .and_then(|default| default.arguments.as_deref());
+
+        if field.field_type == "DateTime"
+            && (default == Some("now()") || field.attribute("updatedAt").is_some())
+        {
+            options.push("onCreate: () => new Date()".to_string());
+        }
+
+        if field.attribute("updatedAt").is_some() {
+            options.push("onUpdate: () => new Date()".to_string());
+        }
+
+        let decorator = if field.name == "id" {
+            "PrimaryKey"
+        } else if field.is_enum() {
+            options.insert(
+                0,
+                format!(
+                    "items: [{}]",
+                    field
+                        .enum_values
+                        .iter()
+                        .map(|value| format!("'{}'", value))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            );
+
+            "Enum"
+        } else {
+            "Property"
+        };
+
+        if !decorators.contains(&decorator) {
+            decorators.push(decorator);
+        }
+
+        let property_type = if field.is_enum() {
+            field
+                .enum_values
+                .iter()
+                .map(|value| format!("'{}'", value))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        } else {
+            property_type.to_string()
+        };
+        let property_type = if field.is_list {
+            format!("{}[]", property_type)
+        } else {
+            property_type
+        };
+
+        // Dates are handled by the `onCreate` hook, the other defaults are
+        // property initializers, as MikroORM recommends.
+        let initializer = match field.field_type.as_str() {
+            "DateTime" | "Decimal" | "BigInt" => None,
+            // Entities hold the database values of enums, not the domain ones.
+            _ if field.is_enum() => default
+                .filter(|default| field.enum_values.iter().any(|value| value == default))
+                .map(|default| format!("'{}'", default)),
+            _ => default_value(field, config),
+        };
+
+        let declaration = match (initializer, field.is_optional) {
+            (Some(initializer), _) => format!("{}: {} = {}", name, property_type, initializer),
+            (None, true) => format!("{}?: {}", name, property_type),
+            (None, false) => format!("{}!: {}", name, property_type),
+        };
+
+        properties.push(format!(
+            "{}@{}({})\n{}{}",
+            style.indent(1),
+            decorator,
+            if options.is_empty() {
+                String::new()
+            } else {
+                format!("{{ {} }}", options.join(", "))
+            },
+            style.indent(1),
+            declaration
+        ));
+    }
+
+    format!(
+        "import {{ {} }} from '@mikro-orm/core'\n\n@Entity({{ tableName: '{}' }})\n{} class {} {{\n{}\n}}\n",
+        decorators.join(", "),
+        model.table_name(),
+        export_keyword(config),
+        config.class_name(&model.name),
+        properties.join("\n\n")
+    )
+}
+
+/// MikroORM column type and TypeScript type of a field. Decimals and big
+/// integers are hydrated as strings by MikroORM to keep their precision.
+fn mikro_orm_type(field: &Field) -> Option<(&'static str, &'static str)> {
+    if field.is_enum() {
+        return Some(("enum", "string"));
+    }
+
+    Some(match field.field_type.as_str() {
+        "String" => ("string", "string"),
+        "Int" => ("integer", "number"),
+        "Float" => ("double", "number"),
+        "Decimal" => ("decimal", "string"),
+        "BigInt" => ("bigint", "string"),
+        "Boolean" => ("boolean", "boolean"),
+        "DateTime" => ("datetime", "Date"),
+        "Json" => ("json", "unknown"),
+        "Bytes" => ("blob", "Buffer"),
+        _ => return None,
+    })
+}
+
+fn json_schema_type(field: &Field, config: &Config) -> Option<Value> {
+    if field.is_enum() {
+        let values: Vec<String> = field
+            .enum_values
+            .iter()
+            .map(|value| config.domain_enum_value(&field.field_type, value))
+            .collect();
+
+        return Some(json!({ "enum": values }));
+    }
+
+    Some(match (field.field_type.as_str(), &config.decimal_type) {
+        ("String", _) | ("Decimal", DecimalType::String) => json!({ "type": "string" }),
+        ("Int" | "BigInt", _) => json!({ "type": "integer" }),
+        ("Float" | "Decimal", _) => json!({ "type": "number" }),
+        ("Boolean", _) => json!({ "type": "boolean" }),
+        ("DateTime", _) => json!({ "type": "string", "format": "date-time" }),
+        // Any JSON value.
+        ("Json", _) => json!({}),
+        _ => return None,
+    })
+}
+
+/// Literal standing in for an absent optional value in the domain layer.
+fn absent_value(config: &Config) -> &'static str {
+    match config.absent_value {
+        AbsentValue::Null => "null",
+        AbsentValue::Undefined => "undefined",
+    }
+}
+
+/// Type of a DTO property, with the configured DTO nullability or the
+/// entity's absent value.
+fn dto_type_annotation(field: &Field, field_type: &str, config: &Config) -> String {
+    match config.dto_nullability {
+        Some(DtoNullability::Nullable) => {
+            type_annotation(field_type, field.is_list, false, config)
+                + if field.is_optional { " | null" } else { "" }
+        }
+        Some(DtoNullability::Optional) => type_annotation(field_type, field.is_list, false, config),
+        None => type_annotation(field_type, field.is_list, field.is_optional, config),
+    }
+}
+
+fn type_annotation(field_type: &str, is_list: bool, is_optional: bool, config: &Config) -> String {
+    let field_type = if is_list {
+        format!("{}[]", field_type)
+    } else {
+        field_type.to_string()
+    };
+
+    if is_optional {
+        return format!("{} | {}", field_type, absent_value(config));
+    }
+
+    field_type
+}
+
+fn build_type_string(
+    field_type: &str,
+    field_name: &str,
+    is_list: bool,
+    is_optional: bool,
+    read_only: bool,
+    config: &Config,
+) -> String {
+    let style = config.indent_style;
+
+    let mut formatted_field_type = String::new();
+    if read_only {
+        write!(
+            formatted_field_type,
+            "\n{}readonly {}: ",
+            style.indent(1),
+            field_name
+        )
+        .unwrap();
+    } else {
+        write!(
+            formatted_field_type,
+            "\n{}{}: ",
+            style.indent(1),
+            field_name
+        )
+        .unwrap();
+    };
+
+    formatted_field_type.push_str(&type_annotation(field_type, is_list, is_optional, config));
+
+    formatted_field_type
+}
+
+fn field_type_name<'a>(field: &'a Field, config: &'a Config) -> Option<&'a str> {
+    // Enums are declared as string unions in the entity file, matching the
+    // values Prisma Client returns.
+    if field.is_enum() {
+        return Some(&field.field_type);
+    }
+
+    match field.field_type.as_str() {
+        "Decimal" if matches!(config.decimal_type, DecimalType::String) => Some("string"),
+        "Float" | "Int" | "Decimal" | "BigInt" => Some("number"),
+        "String" => Some("string"),
+        "Boolean" => Some("boolean"),
+        "Json" => Some(config.json_type()),
+        "Bytes" => Some(match config.bytes_type {
+            BytesType::Buffer => "Buffer",
+            BytesType::Uint8Array => "Uint8Array",
+        }),
+        "DateTime" => Some(match config.date_time_type {
+            DateTimeType::Date => "Date",
+            DateTimeType::Instant => "Temporal.Instant",
+            DateTimeType::PlainDateTime => "Temporal.PlainDateTime",
+        }),
+        _ => None,
+    }
+}
+
+/// Entity property for a field, renamed when the field name collides with a
+/// reserved class member.
+fn property_name(field: &Field, config: &Config) -> String {
+    if RESERVED_PROPERTY_NAMES.contains(&field.name.as_str()) {
+        return format!("{}{}", field.name, config.reserved_name_suffix());
+    }
+
+    field.name.clone()
+}
+
+/// Branded type of the model's `id`, when `brandedIds` is set.
+fn branded_id(model: &Model, config: &Config) -> Option<String> {
+    if !config.branded_ids {
+        return None;
+    }
+
+    model
+        .fields
+        .iter()
+        .find(|field| field.name == "id" && field_type_name(field, config).is_some())
+        .map(|_| format!("{}Id", config.class_name(&model.name)))
+}
+
+/// Type of the `id` parameters: the entity's `id` property type, branded
+/// when configured.
+fn id_type(model: &Model, config: &Config) -> String {
+    model
+        .fields
+        .iter()
+        .find(|field| field.name == "id")
+        .and_then(|id| property_type(model, id, config))
+        // The methods taking an `id` look it up by that field name, so a model
+        // without one has no type to go by.
+        .unwrap_or_else(|| "string".to_string())
+}
+
+/// Type of an entity property, with the model's `id` branded when
+/// configured.
+fn property_type(model: &Model, field: &Field, config: &Config) -> Option<String> {
+    let field_type = field_type_name(field, config)?;
+
+    if field.name == "id" {
+        if let Some(id_type) = branded_id(model, config) {
+            return Some(id_type);
+        }
+    }
+
+    Some(field_type.to_string())
+}
+
+fn get_field_with_type(
+    model: &Model,
+    field: &Field,
+    read_only: bool,
+    config: &Config,
+) -> Option<String> {
+    property_type(model, field, config).map(|field_type| {
+        build_type_string(
+            &field_type,
+            &property_name(field, config),
+            field.is_list,
+            field.is_optional,
+            read_only,
+            config,
+        )
+    })
+}
+
+fn to_kebab_case(name: &str) -> String {
+    let mut kebab_case_string = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            kebab_case_string.push('-');
+        }
+        kebab_case_string.push(ch.to_ascii_lowercase());
+    }
+
+    kebab_case_string
+}
+
+fn to_pascal_case(kebab_case: &str) -> String {
+    kebab_case.split('-').map(uppercase_first_char).collect()
+}
+
+fn module_dir(dir: &Path, module_path: &str, path: &str) -> String {
+    format!(
+        "{}/{}{}",
+        dir.display(),
+        module_path,
+        path.trim_end_matches('/')
+    )
+}
+
+/// Re-exports every entity file in the entity directory, so models generated
+/// in earlier runs stay listed alongside the one being generated.
+fn create_entity_barrel(entity_dir: &str, generated: &str, config: &Config) -> String {
+    let mut entity_files: Vec<String> = fs::read_dir(entity_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .strip_suffix(".entity.ts")
+                .map(String::from)
+        })
+        .collect();
+    entity_files.push(generated.to_string());
+    entity_files.sort();
+    entity_files.dedup();
+
+    let mut barrel = String::new();
+
+    for file in &entity_files {
+        let specifier = module_specifier(&format!("./{}.entity", file), config);
+
+        writeln!(barrel, "export * from '{}'", specifier).unwrap();
+
+        if matches!(config.export_style, ExportStyle::Default) && !config.contracts_only {
+            writeln!(
+                barrel,
+                "export {{ default as {} }} from '{}'",
+                to_pascal_case(file),
+                specifier
+            )
+            .unwrap();
+        }
+    }
+
+    barrel
+}
+
+fn create_types_barrel(config: &Config) -> String {
+    let entity_dir = format!("./{}", ENTITY_PATH);
+
+    // ESM doesn't resolve directory imports to their index file.
+    let specifier = match config.module_system {
+        ModuleSystem::CommonJs => entity_dir,
+        ModuleSystem::Esm => module_specifier(&format!("{}/index", entity_dir), config),
+    };
+
+    format!("export * as Entities from '{}'\n", specifier)
+}
+
+/// Specifier for a relative import of a generated file, given without its
+/// extension. ESM resolution needs the extension of the emitted `.js` file.
+fn module_specifier(path: &str, config: &Config) -> String {
+    match config.module_system {
+        ModuleSystem::CommonJs => path.to_string(),
+        ModuleSystem::Esm => format!("{}.js", path),
+    }
+}
+
+/// Bounded context of a model, from the config or its `/// @context` comment.
+fn model_context<'a>(model: &'a Model, config: &'a Config) -> Option<&'a str> {
+    config
+        .model(&model.name)
+        .and_then(|model_config| model_config.context.as_deref())
+        .or(model.context.as_deref())
+}
+
+/// Module path of a model's files, nested under its bounded context when it
+/// has one.
+fn model_module_path(module_path: &str, model: &Model, config: &Config) -> String {
+    match model_context(model, config) {
+        Some(context) => format!("{}{}/{}/", module_path, CONTEXTS_PATH, context),
+        None => module_path.to_string(),
+    }
+}
+
+fn build_path(
+    dir: &Path,
+    module_path: &str,
+    module_type: ModuleType,
+    model_name: &str,
+    config: &Config,
+) -> String {
+    let (path, file_name) = module_location(module_type, model_name, config);
+
+    format!("{}/{}{}/{}", dir.display(), module_path, path, file_name)
+}
+
+/// Directory under the module path and file name of a generated module.
+fn module_location<'a>(
+    module_type: ModuleType,
+    model_name: &str,
+    config: &'a Config,
+) -> (&'a str, String) {
+    let kebab_model_name = to_kebab_case(model_name);
+
+    match module_type {
+        ModuleType::Entity => (ENTITY_PATH, format!("{}.entity.ts", kebab_model_name)),
+        ModuleType::Mapper => (MAPPER_PATH, format!("{}.mapper.ts", kebab_model_name)),
+        ModuleType::Repository(_) => (
+            REPOSITORY_PATH,
+            format!("{}.repository.ts", kebab_model_name),
+        ),
+        ModuleType::PrismaRepository => (
+            PRISMA_REPOSITORY_PATH,
+            format!("prisma-{}.repository.ts", kebab_model_name),
+        ),
+        ModuleType::PrismaService => (PRISMA_SERVICE_PATH, "prisma.service.ts".to_string()),
+        ModuleType::QueryDto => (DTO_PATH, format!("list-{}-query.dto.ts", kebab_model_name)),
+        ModuleType::Dto => (DTO_PATH, format!("{}.dto.ts", kebab_model_name)),
+        ModuleType::JsonSchema => (SCHEMA_PATH, format!("{}.schema.json", kebab_model_name)),
+        ModuleType::MikroOrmEntity => (
+            MIKRO_ORM_ENTITY_PATH,
+            format!("{}.entity.ts", kebab_model_name),
+        ),
+        ModuleType::IntegrationSpec => (
+            PRISMA_REPOSITORY_PATH,
+            format!("prisma-{}.repository.int-spec.ts", kebab_model_name),
+        ),
+        ModuleType::Events => (
+            config
+                .events
+                .as_ref()
+                .and_then(|events| events.path.as_deref())
+                .unwrap_or(EVENTS_PATH),
+            format!("{}.events.ts", kebab_model_name),
+        ),
+        ModuleType::Constants => (CONSTANTS_PATH, format!("{}.constants.ts", kebab_model_name)),
+    }
+}
+
+/// Relative specifier for importing `to` from a file of `from`. Both live
+/// under the same module path, so it cancels out.
+fn relative_specifier(
+    from: ModuleType,
+    to: ModuleType,
+    model_name: &str,
+    config: &Config,
+) -> String {
+    let (from_dir, _) = module_location(from, model_name, config);
+    let (to_dir, file_name) = module_location(to, model_name, config);
+
+    let from_dir: Vec<&str> = from_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let to_dir: Vec<&str> = to_dir.split('/').filter(|part| !part.is_empty()).collect();
+
+    let common = from_dir
+        .iter()
+        .zip(&to_dir)
+        .take_while(|(from, to)| from == to)
+        .count();
+
+    let mut parts: Vec<&str> = vec![".."; from_dir.len() - common];
+    parts.extend(&to_dir[common..]);
+
+    let path = if parts.first() == Some(&"..") {
+        parts.join("/")
+    } else {
+        std::iter::once(".")
+            .chain(parts)
+            .collect::<Vec<&str>>()
+            .join("/")
+    };
+
+    module_specifier(
+        &format!("{}/{}", path, file_name.trim_end_matches(".ts")),
+        config,
+    )
+}
+
+/// Whether `contents` references `symbol` as a whole identifier.
+fn references(contents: &str, symbol: &str) -> bool {
+    let is_identifier = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '$';
+
+    contents.match_indices(symbol).any(|(index, _)| {
+        let before = contents[..index].chars().next_back();
+        let after = contents[index + symbol.len()..].chars().next();
+
+        !before.is_some_and(is_identifier) && !after.is_some_and(is_identifier)
+    })
+}
+
+/// Import lines for the generated classes, Prisma types and framework
+/// symbols a generated module references.
+fn module_imports(
+    module: ModuleType,
+    model: &Model,
+    config: &Config,
+    contents: &str,
+) -> Vec<String> {
+    let class_name = config.class_name(&model.name);
+    let default_export = matches!(config.export_style, ExportStyle::Default);
+    let generated = |symbol: String, to: ModuleType| {
+        let clause = if default_export {
+            symbol.clone()
+        } else {
+            format!("{{ {} }}", symbol)
+        };
+
+        (symbol, clause, Some(to))
+    };
+
+    let mut candidates: Vec<(String, String, Option<ModuleType>)> = Vec::new();
+
+    for (symbol, clause) in [
+        (
+            format!("Prisma{}", model.name),
+            format!("{{ {} as Prisma{} }}", model.name, model.name),
+        ),
+        ("PrismaClient".to_string(), "{ PrismaClient }".to_string()),
+        ("Prisma".to_string(), "{ Prisma }".to_string()),
+        ("Injectable".to_string(), "{ Injectable }".to_string()),
+        ("Inject".to_string(), "{ Inject }".to_string()),
+        ("CACHE_MANAGER".to_string(), "{ CACHE_MANAGER }".to_string()),
+        ("Cache".to_string(), "{ Cache }".to_string()),
+        ("Cacheable".to_string(), "{ Cacheable }".to_string()),
+        ("CacheEvict".to_string(), "{ CacheEvict }".to_string()),
+        ("EventEmitter2".to_string(), "{ EventEmitter2 }".to_string()),
+    ] {
+        candidates.push((symbol, clause, None));
+    }
+
+    candidates.extend([
+        generated(class_name.clone(), ModuleType::Entity),
+        (
+            format!("I{}", class_name),
+            format!("{{ I{} }}", class_name),
+            Some(ModuleType::Entity),
+        ),
+        (
+            format!("{}Id", class_name),
+            format!("{{ {}Id }}", class_name),
+            Some(ModuleType::Entity),
+        ),
+        generated(format!("{}Mapper", class_name), ModuleType::Mapper),
+        generated(
+            format!("{}Repository", class_name),
+            ModuleType::Repository(None),
+        ),
+        generated(
+            format!("Prisma{}Repository", class_name),
+            ModuleType::PrismaRepository,
+        ),
+        generated("PrismaService".to_string(), ModuleType::PrismaService),
+    ]);
+
+    for event in ["Created", "Updated", "Deleted", "Restored"] {
+        let symbol = format!("{}{}Event", class_name, event);
+        let clause = format!("{{ {} }}", symbol);
+
+        candidates.push((symbol, clause, Some(ModuleType::Events)));
+    }
+
+    // Discriminator subclasses are the project's own, each expected in an
+    // entity file of its own name next to the model's.
+    let subclasses: Vec<&String> = config
+        .model(&model.name)
+        .and_then(|model_config| model_config.discriminator.as_ref())
+        .map(|discriminator| discriminator.classes.values().collect())
+        .unwrap_or_default();
+
+    for subclass in &subclasses {
+        candidates.push(generated(subclass.to_string(), ModuleType::Entity));
+    }
+
+    let mut imports: Vec<(String, Vec<String>)> = Vec::new();
+
+    for (symbol, clause, to) in candidates {
+        if !references(contents, &symbol) {
+            continue;
+        }
+
+        let source = match to {
+            // A module never imports itself.
+            Some(to) if to == module => continue,
+            Some(to) => {
+                let to_name = if to == ModuleType::PrismaService {
+                    &model.name
+                } else if subclasses.contains(&&symbol) {
+                    &symbol
+                } else {
+                    &class_name
+                };
+
+                relative_specifier(module.clone(), to, to_name, config)
+            }
+            None => match (symbol.as_str(), &config.cache_decorators) {
+                ("Injectable" | "Inject", _) => "@nestjs/common".to_string(),
+                ("CACHE_MANAGER", _) => "@nestjs/cache-manager".to_string(),
+                ("EventEmitter2", _) => "@nestjs/event-emitter".to_string(),
+                ("Cache", _) => "cache-manager".to_string(),
+                ("Cacheable" | "CacheEvict", Some(decorators)) => decorators.module.clone(),
+                // Not generated without the config, so it's a name of the
+                // project's own.
+                ("Cacheable" | "CacheEvict", None) => continue,
+                _ => "@prisma/client".to_string(),
+            },
+        };
+
+        match imports.iter_mut().find(|(other, _)| *other == source) {
+            Some((_, clauses)) => clauses.push(clause),
+            None => imports.push((source, vec![clause])),
+        }
+    }
+
+    // The scope context is a class of the project's own, injected by type.
+    if let Some(scope) = base_scope(model, config) {
+        if references(contents, &scope.context) {
+            imports.push((
+                scope.module.clone(),
+                vec![format!("{{ {} }}", scope.context)],
+            ));
+        }
+    }
+
+    imports
+        .into_iter()
+        .map(|(source, clauses)| {
+            // Named imports from the same module share one statement.
+            let (named, default): (Vec<String>, Vec<String>) = clauses
+                .into_iter()
+                .partition(|clause| clause.starts_with('{'));
+            let mut parts = default;
+
+            if !named.is_empty() {
+                let names: Vec<&str> = named
+                    .iter()
+                    .map(|clause| clause.trim_matches(|ch| ch == '{' || ch == '}').trim())
+                    .collect();
+                parts.push(format!("{{ {} }}", names.join(", ")));
+            }
+
+            format!("import {} from '{}'", parts.join(", "), source)
+        })
+        .collect()
+}
+
+fn prepend_imports(imports: &[String], contents: String) -> String {
+    let mut unique_imports: Vec<&str> = Vec::new();
+
+    for import in imports {
+        let import = import.trim();
+        if !unique_imports.contains(&import) && !contents.lines().any(|line| line.trim() == import)
+        {
+            unique_imports.push(import);
+        }
+    }
+
+    if unique_imports.is_empty() {
+        return contents;
+    }
+
+    format!("{}\n\n{}", unique_imports.join("\n"), contents)
+}
+
+/// Groups the imports at the top of a generated file in the configured
+/// order, keeping the order within each group.
+fn order_imports(contents: String, config: &Config) -> String {
+    let block_len = contents
+        .lines()
+        .take_while(|line| line.starts_with("import "))
+        .count();
+
+    if block_len < 2 {
+        return contents;
+    }
+
+    let internal_prefixes = config.internal_import_prefixes();
+    let order = config.import_order();
+    let rank = |import: &&str| {
+        let specifier = import
+            .rsplit_once(" from ")
+            .map_or(*import, |(_, specifier)| specifier)
+            .trim_matches(|ch| ch == '\'' || ch == '"' || ch == ';');
+        let group = if specifier.starts_with('.') {
+            ImportGroup::Relative
+        } else if internal_prefixes
+            .iter()
+            .any(|prefix| specifier.starts_with(prefix))
+        {
+            ImportGroup::Internal
+        } else {
+            ImportGroup::External
+        };
+
+        order
+            .iter()
+            .position(|other| *other == group)
+            .unwrap_or(order.len())
+    };
+
+    let mut lines: Vec<&str> = contents.split('\n').collect();
+    lines[..block_len].sort_by_key(rank);
+
+    lines.join("\n")
+}
+
+/// Prepends the imports a generated module needs, followed by the extra
+/// imports configured for it.
+fn with_imports(
+    module: ModuleType,
+    model: &Model,
+    config: &Config,
+    extra_imports: &[String],
+    contents: String,
+) -> String {
+    let mut imports = module_imports(module, model, config, &contents);
+    imports.extend(extra_imports.iter().cloned());
+
+    prepend_imports(&imports, contents)
+}
+
+/// Carries the hand-written code between the custom markers of an existing
+/// file over into its regenerated contents.
+fn merge_custom_code(path: &str, contents: String) -> String {
+    let Ok(existing) = fs::read_to_string(path) else {
+        return contents;
+    };
+
+    // The region ends before the end marker's line, which stays generated.
+    let custom_region = |source: &str| {
+        let start = source.find(CUSTOM_CODE_START)? + CUSTOM_CODE_START.len();
+        let end = start + source[start..].find(CUSTOM_CODE_END)?;
+        let end = source[start..end]
+            .rfind('\n')
+            .map_or(end, |line| start + line);
+        Some((start, end))
+    };
+
+    match (custom_region(&existing), custom_region(&contents)) {
+        (Some((existing_start, existing_end)), Some((start, end))) => format!(
+            "{}{}{}",
+            &contents[..start],
+            &existing[existing_start..existing_end],
+            &contents[end..]
+        ),
+        _ => contents,
+    }
+}
+
+/// Post-generation hook applied to every file's contents before it is written.
+pub type Transform = Box<dyn Fn(&Path, &str) -> String>;
+
+/// Transform prepending the generator version and source schema to each
+/// file. There's no timestamp, so regenerating unchanged code doesn't show up
+/// in diffs.
+pub fn metadata_header(schema_path: &Path) -> Transform {
+    let header = format!(
+        "// Generated by entity-generator v{}\n// Source: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        schema_path.display()
+    );
+
+    Box::new(move |path, contents| {
+        // JSON has no comments.
+        if contents.starts_with(&header) || path.extension().is_some_and(|ext| ext == "json") {
+            return contents.to_string();
+        }
+
+        format!("{}\n{}", header, contents)
+    })
+}
+
+/// Where generated files go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    Disk,
+    /// Prints each file under its path instead of writing it, to preview
+    /// the output without touching the project.
+    Stdout,
+}
+
+/// What became of a generated file, for the caller to report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    Written,
+    /// Left as it was, per the overwrite policy.
+    Skipped,
+}
+
+fn write_to_module<P: AsRef<Path>>(
+    path: P,
+    contents: String,
+    transforms: &[Transform],
+    overwrite: OverwritePolicy,
+    mode: WriteMode,
+) -> std::io::Result<WriteOutcome> {
+    let contents = transforms.iter().fold(contents, |contents, transform| {
+        transform(path.as_ref(), &contents)
+    });
+
+    let skipped = match overwrite {
+        OverwritePolicy::Always => false,
+        OverwritePolicy::SkipExisting => path.as_ref().exists(),
+        OverwritePolicy::IfChanged => {
+            fs::read_to_string(&path).is_ok_and(|existing| existing == contents)
+        }
+    };
+
+    if skipped {
+        return Ok(WriteOutcome::Skipped);
+    }
+
+    if mode == WriteMode::Stdout {
+        println!("==> {} <==\n{}", path.as_ref().display(), contents);
+
+        return Ok(WriteOutcome::Written);
+    }
+
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = fs::File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+
+    Ok(WriteOutcome::Written)
+}
+
+/// Rendered templates keyed by a hash of their inputs, so a model generated
+/// for several output targets is only rendered once.
+#[derive(Default)]
+struct TemplateCache {
+    templates: HashMap<u64, Box<dyn Any>>,
+}
+
+impl TemplateCache {
+    /// Renders a template for `model`, reusing the cached output when the
+    /// model, config and `inputs` are unchanged.
+    fn render<T: Clone + 'static>(
+        &mut self,
+        template: &str,
+        model: &Model,
+        config: &Config,
+        inputs: impl Hash,
+        render: impl FnOnce() -> T,
+    ) -> T {
+        let mut hasher = DefaultHasher::new();
+        template.hash(&mut hasher);
+        format!("{:?}", model).hash(&mut hasher);
+        // JSON objects keep their keys sorted, unlike the config's hash maps.
+        serde_json::to_string(&serde_json::to_value(config).unwrap())
+            .unwrap()
+            .hash(&mut hasher);
+        inputs.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some(cached) = self
+            .templates
+            .get(&key)
+            .and_then(|cached| cached.downcast_ref::<T>())
+        {
+            return cached.clone();
+        }
+
+        let rendered = render();
+        self.templates.insert(key, Box::new(rendered.clone()));
+
+        rendered
+    }
+}
+
+fn target_files(
+    target: &OutputTarget,
+    dir: &Path,
+    model: &Model,
+    config: &Config,
+    templates: &mut TemplateCache,
+) -> Vec<(String, String)> {
+    let OutputTarget {
+        modules,
+        module_path,
+    } = target;
+    let module_path = &model_module_path(module_path, model, config);
+    let class_name = config.class_name(&model.name);
+    let has_mapper = modules.contains(&ModuleType::Mapper);
+    let has_entity = modules.contains(&ModuleType::Entity);
+    let mut files: Vec<(String, String)> = Vec::new();
+
+    for module in modules {
+        // Contract packages only carry the interfaces and abstract
+        // repositories, not their implementations.
+        if config.contracts_only
+            && matches!(
+                module,
+                ModuleType::Mapper
+                    | ModuleType::QueryDto
+                    | ModuleType::Dto
+                    | ModuleType::PrismaService
+                    | ModuleType::MikroOrmEntity
+                    | ModuleType::IntegrationSpec
+            )
+        {
+            continue;
+        }
+
+        // Views can't be written to, so there is nothing to take input for.
+        if model.is_view && *module == ModuleType::Dto {
+            continue;
+        }
+
+        match module {
+            ModuleType::Entity => {
+                files.push((
+                    build_path(dir, module_path, ModuleType::Entity, &class_name, config),
+                    with_imports(
+                        ModuleType::Entity,
+                        model,
+                        config,
+                        &config.extra_imports.entity,
+                        templates.render("entity", model, config, has_mapper, || {
+                            create_entity(model, has_mapper, config)
+                        }),
+                    ),
+                ));
+
+                if config.types_barrel {
+                    let entity_dir = module_dir(dir, module_path, ENTITY_PATH);
+
+                    files.push((
+                        format!("{}/index.ts", entity_dir),
+                        create_entity_barrel(&entity_dir, &to_kebab_case(&class_name), config),
+                    ));
+
+                    files.push((
+                        format!("{}/{}{}", dir.display(), module_path, TYPES_BARREL_FILE),
+                        create_types_barrel(config),
+                    ));
+                }
+            }
+            ModuleType::Mapper => files.push((
+                build_path(dir, module_path, ModuleType::Mapper, &class_name, config),
+                with_imports(
+                    ModuleType::Mapper,
+                    model,
+                    config,
+                    &config.extra_imports.mapper,
+                    templates.render("mapper", model, config, (), || create_mapper(model, config)),
+                ),
+            )),
+            ModuleType::Repository(methods) => {
+                let (abstract_repository, prisma_repository) = templates.render(
+                    "repository",
+                    model,
+                    config,
+                    (format!("{:?}", methods), has_mapper, has_entity),
+                    || create_repository(model, methods.clone(), has_mapper, has_entity, config),
+                );
+
+                let abstract_repository_path = build_path(
+                    dir,
+                    module_path,
+                    ModuleType::Repository(None),
+                    &class_name,
+                    config,
+                );
+                let prisma_repository_path = build_path(
+                    dir,
+                    module_path,
+                    ModuleType::PrismaRepository,
+                    &class_name,
+                    config,
+                );
+
+                let abstract_repository = with_imports(
+                    ModuleType::Repository(None),
+                    model,
+                    config,
+                    &config.extra_imports.repository,
+                    abstract_repository,
+                );
+                let prisma_repository = with_imports(
+                    ModuleType::PrismaRepository,
+                    model,
+                    config,
+                    &config.extra_imports.prisma_repository,
+                    prisma_repository,
+                );
+
+                let (abstract_repository, prisma_repository) = if config.preserve_custom_code {
+                    (
+                        merge_custom_code(&abstract_repository_path, abstract_repository),
+                        merge_custom_code(&prisma_repository_path, prisma_repository),
+                    )
+                } else {
+                    (abstract_repository, prisma_repository)
+                };
+
+                files.push((abstract_repository_path, abstract_repository));
+
+                if !config.contracts_only {
+                    files.push((prisma_repository_path, prisma_repository));
+                }
+
+                if config.events.is_some() {
+                    files.push((
+                        build_path(dir, module_path, ModuleType::Events, &class_name, config),
+                        with_imports(
+                            ModuleType::Events,
+                            model,
+                            config,
+                            &[],
+                            templates.render(
+                                "events",
+                                model,
+                                config,
+                                (format!("{:?}", methods), has_entity),
+                                || create_events(model, methods.as_deref(), has_entity, config),
+                            ),
+                        ),
+                    ));
+                }
+            }
+            ModuleType::QueryDto => files.push((
+                build_path(dir, module_path, ModuleType::QueryDto, &model.name, config),
+                templates.render("query dto", model, config, (), || {
+                    create_list_query_dto(model, config)
+                }),
+            )),
+            ModuleType::Dto => files.push((
+                build_path(dir, module_path, ModuleType::Dto, &model.name, config),
+                templates.render("dto", model, config, (), || create_dto(model, config)),
+            )),
+            ModuleType::JsonSchema => files.push((
+                build_path(
+                    dir,
+                    module_path,
+                    ModuleType::JsonSchema,
+                    &class_name,
+                    config,
+                ),
+                templates.render("json schema", model, config, (), || {
+                    create_json_schema(model, config)
+                }),
+            )),
+            ModuleType::MikroOrmEntity => files.push((
+                build_path(
+                    dir,
+                    module_path,
+                    ModuleType::MikroOrmEntity,
+                    &class_name,
+                    config,
+                ),
+                templates.render("mikro-orm entity", model, config, (), || {
+                    create_mikro_orm_entity(model, config)
+                }),
+            )),
+            ModuleType::Constants => files.push((
+                build_path(dir, module_path, ModuleType::Constants, &class_name, config),
+                templates.render("constants", model, config, (), || {
+                    create_constants(model, config)
+                }),
+            )),
+            ModuleType::IntegrationSpec => {
+                // Only the methods generated alongside are known, a repository
+                // generated earlier is assumed to have the default ones.
+                let methods = modules.iter().find_map(|module| match module {
+                    ModuleType::Repository(methods) => methods.clone(),
+                    _ => None,
+                });
+
+                files.push((
+                    build_path(
+                        dir,
+                        module_path,
+                        ModuleType::IntegrationSpec,
+                        &class_name,
+                        config,
+                    ),
+                    with_imports(
+                        ModuleType::IntegrationSpec,
+                        model,
+                        config,
+                        &[],
+                        templates.render(
+                            "integration spec",
+                            model,
+                            config,
+                            format!("{:?}", methods),
+                            || create_integration_spec(model, methods.as_deref(), config),
+                        ),
+                    ),
+                ));
+            }
+            ModuleType::PrismaService => {
+                let path = build_path(
+                    dir,
+                    module_path,
+                    ModuleType::PrismaService,
+                    &model.name,
+                    config,
+                );
+
+                if !Path::new(&path).exists() {
+                    files.push((path, create_prisma_service(config)));
+                }
+            }
+            // Written together with the abstract repository.
+            ModuleType::PrismaRepository | ModuleType::Events => {}
+        }
+    }
+
+    files
+}
+
+/// A generated file that couldn't be written.
+#[derive(Debug)]
+pub struct WriteError {
+    pub path: String,
+    pub model: Option<String>,
+    pub source: std::io::Error,
+}
+
+impl WriteError {
+    fn new(path: impl Into<String>, model: Option<&Model>, source: std::io::Error) -> Self {
+        WriteError {
+            path: path.into(),
+            model: model.map(|model| model.name.clone()),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.model {
+            Some(model) => write!(
+                f,
+                "could not write {} for model {}: {}",
+                self.path, model, self.source
+            ),
+            None => write!(f, "could not write {}: {}", self.path, self.source),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+pub fn write_modules(
+    targets: Vec<OutputTarget>,
+    dir: &Path,
+    model: &Model,
+    config: &Config,
+    transforms: &[Transform],
+    mode: WriteMode,
+) -> Result<Vec<(String, WriteOutcome)>, WriteError> {
+    let mut templates = TemplateCache::default();
+    let files: Vec<(String, String)> = targets
+        .iter()
+        .flat_map(|target| target_files(target, dir, model, config, &mut templates))
+        .map(|(path, contents)| (path, order_imports(contents, config)))
+        .collect();
+
+    #[cfg(feature = "validate-imports")]
+    let paths: Vec<String> = files.iter().map(|(path, _)| path.clone()).collect();
+
+    let mut outcomes = Vec::new();
+
+    for (path, contents) in files {
+        let outcome = write_to_module(&path, contents, transforms, config.overwrite, mode)
+            .map_err(|err| WriteError::new(path.clone(), Some(model), err))?;
+
+        outcomes.push((path, outcome));
+    }
+
+    // Nothing was written to check against in a dry run.
+    #[cfg(feature = "validate-imports")]
+    if mode == WriteMode::Stdout {
+        return Ok(outcomes);
+    }
+
+    #[cfg(feature = "validate-imports")]
+    for unresolved in crate::validate::unresolved_imports(&paths) {
+        eprintln!(
+            "{}: unresolved import '{}'",
+            unresolved.path, unresolved.source
+        );
+    }
+
+    Ok(outcomes)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelReport<'a> {
+    name: &'a str,
+    field_count: usize,
+    generated_modules: Vec<&'static str>,
+    skipped_fields: Vec<SkippedField<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SkippedField<'a> {
+    name: &'a str,
+    field_type: &'a str,
+}
+
+/// Codegen coverage of every model in the schema: which modules exist under
+/// the module path and which fields the generator leaves out.
+fn create_report(models: &[Model], dir: &Path, module_path: &str, config: &Config) -> String {
+    let reports: Vec<ModelReport> = models
+        .iter()
+        .map(|model| {
+            let class_name = config.class_name(&model.name);
+            let module_path = model_module_path(module_path, model, config);
+            let generated_modules = [
+                (ModuleType::Entity, &class_name),
+                (ModuleType::Mapper, &class_name),
+                (ModuleType::Repository(None), &class_name),
+                (ModuleType::PrismaRepository, &class_name),
+                (ModuleType::QueryDto, &model.name),
+                (ModuleType::Dto, &model.name),
+                (ModuleType::JsonSchema, &class_name),
+                (ModuleType::MikroOrmEntity, &class_name),
+                (ModuleType::IntegrationSpec, &class_name),
+                (ModuleType::Events, &class_name),
+                (ModuleType::Constants, &class_name),
+            ]
+            .into_iter()
+            .filter(|(module, name)| {
+                let path = build_path(dir, &module_path, module.clone(), name, config);
+                Path::new(&path).exists()
+            })
+            .map(|(module, _)| module.into())
+            .collect();
+
+            ModelReport {
+                name: &model.name,
+                field_count: model.fields.len(),
+                generated_modules,
+                skipped_fields: model
+                    .fields
+                    .iter()
+                    .filter(|field| field_type_name(field, config).is_none())
+                    .map(|field| SkippedField {
+                        name: &field.name,
+                        field_type: &field.field_type,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&reports).unwrap()
+}
+
+pub fn write_report(
+    models: &[Model],
+    dir: &Path,
+    module_path: &str,
+    config: &Config,
+    mode: WriteMode,
+) -> Result<(), WriteError> {
+    let path = dir.join(REPORT_FILE);
+
+    write_to_module(
+        &path,
+        create_report(models, dir, module_path, config),
+        &[],
+        // The report describes the current schema, it's never hand-edited.
+        OverwritePolicy::Always,
+        mode,
+    )
+    .map(|_| ())
+    .map_err(|err| WriteError::new(path.display().to_string(), None, err))
+}
+
+/// Registry of the abstract repositories generated under the module path,
+/// typed by model name, with the repository classes as DI tokens.
+fn create_repository_registry(
+    models: &[Model],
+    dir: &Path,
+    module_path: &str,
+    config: &Config,
+) -> String {
+    let style = config.indent_style;
+    let class_names: Vec<(&Model, String)> = models
+        .iter()
+        .map(|model| (model, config.class_name(&model.name)))
+        .filter(|(model, class_name)| {
+            let path = build_path(
+                dir,
+                &model_module_path(module_path, model, config),
+                ModuleType::Repository(None),
+                class_name,
+                config,
+            );
+            Path::new(&path).exists()
+        })
+        .collect();
+
+    let mut registry = String::new();
+
+    for (model, class_name) in &class_names {
+        let import = match config.export_style {
+            ExportStyle::Named => format!("{{ {}Repository }}", class_name),
+            ExportStyle::Default => format!("{}Repository", class_name),
+        };
+
+        // Repositories of a bounded context live under its own module path.
+        let directory = match model_context(model, config) {
+            Some(context) => format!(
+                "{}{}/{}/{}",
+                "../".repeat(REPOSITORY_PATH.split('/').count()),
+                CONTEXTS_PATH,
+                context,
+                REPOSITORY_PATH
+            ),
+            None => ".".to_string(),
+        };
+
+        writeln!(
+            registry,
+            "import {} from '{}'",
+            import,
+            module_specifier(
+                &format!("{}/{}.repository", directory, to_kebab_case(class_name)),
+                config
+            )
+        )
+        .unwrap();
+    }
+
+    registry.push_str("\nexport type RepositoryRegistry = {");
+
+    for (model, class_name) in &class_names {
+        write!(
+            registry,
+            "\n{}{}: {}Repository",
+            style.indent(1),
+            model.name,
+            class_name
+        )
+        .unwrap();
+    }
+
+    registry.push_str(
+        &format!(
+            "\n}}\n\nexport const REPOSITORY_TOKENS: {{\n{}[K in keyof RepositoryRegistry]: abstract new (...args: any[]) => RepositoryRegistry[K]\n}} = {{",
+            style.indent(1)
+        ),
+    );
+
+    for (model, class_name) in &class_names {
+        write!(
+            registry,
+            "\n{}{}: {}Repository,",
+            style.indent(1),
+            model.name,
+            class_name
+        )
+        .unwrap();
+    }
+
+    registry.push_str("\n}\n");
+
+    registry
+}
+
+/// Prisma Client extension leaving the soft-deleted rows out of the reads of
+/// every model with a `deletedAt` column.
+fn create_soft_delete_extension(models: &[Model], style: IndentStyle) -> String {
+    let model_names: Vec<String> = models
+        .iter()
+        .filter(|model| is_soft_delete(model))
+        .map(|model| format!("'{}'", model.name))
+        .collect();
+
+    // updateMany covers the repositories' soft-deleting deleteMany too.
+    let operations: Vec<String> = ["findFirst", "findMany", "count", "aggregate", "updateMany"]
+        .iter()
+        .map(|operation| {
+            format!(
+                "{}async {}({{ model, args, query }}) {{\n{}if (SOFT_DELETE_MODELS.includes(model)) {{\n{}args.where = {{ ...args.where, deletedAt: null }}\n{}}}\n\n{}return query(args)\n{}}},",
+                style.indent(3),
+                operation,
+                style.indent(4),
+                style.indent(5),
+                style.indent(4),
+                style.indent(4),
+                style.indent(3)
+            )
+        })
+        .collect();
+
+    format!(
+        "import {{ Prisma }} from '@prisma/client'\n\nconst SOFT_DELETE_MODELS: string[] = [{}]\n\nexport const softDeleteExtension = Prisma.defineExtension({{\n{}name: 'softDelete',\n{}query: {{\n{}$allModels: {{\n{}\n{}}},\n{}}},\n}})\n",
+        model_names.join(", "),
+        style.indent(1),
+        style.indent(1),
+        style.indent(2),
+        operations.join("\n"),
+        style.indent(2),
+        style.indent(1)
+    )
+}
+
+pub fn write_soft_delete_extension(
+    models: &[Model],
+    dir: &Path,
+    module_path: &str,
+    config: &Config,
+    transforms: &[Transform],
+    mode: WriteMode,
+) -> Result<(String, WriteOutcome), WriteError> {
+    let path = config
+        .soft_delete_extension
+        .as_ref()
+        .and_then(|extension| extension.path.as_deref())
+        .unwrap_or(PRISMA_SERVICE_PATH);
+    let path = format!(
+        "{}/{}",
+        module_dir(dir, module_path, path),
+        SOFT_DELETE_EXTENSION_FILE
+    );
+
+    let outcome = write_to_module(
+        &path,
+        create_soft_delete_extension(models, config.indent_style),
+        transforms,
+        config.overwrite,
+        mode,
+    )
+    .map_err(|err| WriteError::new(path.clone(), None, err))?;
+
+    Ok((path, outcome))
+}
+
+pub fn write_repository_registry(
+    models: &[Model],
+    dir: &Path,
+    module_path: &str,
+    config: &Config,
+    transforms: &[Transform],
+    mode: WriteMode,
+) -> Result<(String, WriteOutcome), WriteError> {
+    let path = format!(
+        "{}/{}",
+        module_dir(dir, module_path, REPOSITORY_PATH),
+        REPOSITORY_REGISTRY_FILE
+    );
+
+    let outcome = write_to_module(
+        &path,
+        create_repository_registry(models, dir, module_path, config),
+        transforms,
+        config.overwrite,
+        mode,
+    )
+    .map_err(|err| WriteError::new(path.clone(), None, err))?;
+
+    Ok((path, outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+model User {
+  id        String    @id @default(uuid())
+  email     String    @unique
+  name      String?
+  deletedAt DateTime?
+}
+
+model Tag {
+  id   Int    @id @default(autoincrement())
+  name String
+}
+
+model Account {
+  id        Int      @id @default(autoincrement())
+  email     String   @map("email_address")
+  balance   Decimal
+  createdAt DateTime @default(now())
+
+  @@map("accounts")
+}
+
+model Setting {
+  id      String @id
+  key     String
+  payload Json
+}
+"#;
+
+    fn model(name: &str) -> Model {
+        parse_schema(SCHEMA.as_bytes())
+            .into_iter()
+            .find(|model| model.name == name)
+            .unwrap()
+    }
+
+    fn config(json: &str) -> Config {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn prisma_repository(model: &Model, ops: Vec<RepositoryOperations>, config: &Config) -> String {
+        create_repository(model, Some(ops), true, true, config).1
+    }
+
+    /// Files of a `src/` target in a project at `/project`, with their paths.
+    fn target(modules: Vec<ModuleType>, model: &Model, config: &Config) -> Vec<(String, String)> {
+        target_files(
+            &OutputTarget {
+                modules,
+                module_path: "src/".to_string(),
+            },
+            Path::new("/project"),
+            model,
+            config,
+            &mut TemplateCache::default(),
+        )
+    }
+
+    fn file<'a>(files: &'a [(String, String)], path: &str) -> &'a str {
+        files
+            .iter()
+            .find(|(file_path, _)| file_path == path)
+            .map(|(_, contents)| contents.as_str())
+            .unwrap_or_else(|| panic!("{} not generated", path))
+    }
+
+    #[test]
+    fn repository_factory_returns_the_prisma_repository() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::Create],
+            &config(r#"{ "repositoryFactory": true }"#),
+        );
+
+        assert!(repository.contains(
+            "export function createTagRepository(prisma: PrismaClient): TagRepository {\n\treturn new PrismaTagRepository(prisma as PrismaService)\n}"
+        ));
+    }
+
+    #[test]
+    fn repository_factory_forwards_every_constructor_parameter() {
+        let repository = prisma_repository(
+            &model("User"),
+            vec![RepositoryOperations::Create],
+            &config(
+                r#"{
+                    "repositoryFactory": true,
+                    "cache": { "ttlMs": 1000 },
+                    "events": {},
+                    "models": { "User": { "scope": { "fields": ["name"], "module": "../../../common/query-context" } } }
+                }"#,
+            ),
+        );
+
+        assert!(repository.contains(
+            "export function createUserRepository(prisma: PrismaClient, context: QueryContext, cache: Cache, events: EventEmitter2): UserRepository {\n\treturn new PrismaUserRepository(prisma as PrismaService, context, cache, events)\n}"
+        ));
+    }
+
+    #[test]
+    fn read_write_models_split_the_managed_fields_out() {
+        let entity = create_entity(
+            &model("User"),
+            true,
+            &config(r#"{ "readWriteModels": true }"#),
+        );
+
+        assert!(entity.contains(
+            "export interface UserReadModel {\n\treadonly id: string\n\treadonly email: string\n\treadonly name: string | null\n\treadonly deletedAt: Date | null\n}"
+        ));
+        assert!(entity.contains(
+            "export interface UserWriteModel {\n\temail: string\n\tname?: string | null\n}"
+        ));
+        assert!(entity.contains("implements IUser, UserReadModel"));
+    }
+
+    #[test]
+    fn module_type_from_an_unknown_name_lists_the_known_ones() {
+        assert_eq!(ModuleType::try_from("Mapper"), Ok(ModuleType::Mapper));
+
+        let err = ModuleType::try_from("Controller").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "unknown module type \"Controller\", expected one of: {}",
+                MODULE_TYPE_NAMES.join(", ")
+            )
+        );
+    }
+
+    #[test]
+    fn search_ors_a_contains_per_searchable_field() {
+        let repository = prisma_repository(
+            &model("User"),
+            vec![RepositoryOperations::Search],
+            &config(r#"{ "models": { "User": { "searchableFields": ["email", "name"] } } }"#),
+        );
+
+        assert!(repository.contains(
+            "OR: [\n\t\t\t\t\t{ email: { contains: query } },\n\t\t\t\t\t{ name: { contains: query } },\n\t\t\t\t],"
+        ));
+    }
+
+    #[test]
+    fn default_export_style_exports_the_classes_as_default() {
+        let config = config(r#"{ "exportStyle": "default" }"#);
+
+        assert!(create_entity(&model("User"), true, &config).contains("export default class User "));
+        assert!(create_mapper(&model("User"), &config).contains("export default class UserMapper "));
+    }
+
+    #[test]
+    fn exists_counts_at_most_one_row() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::Exists],
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "async exists(where: Partial<Tag>): Promise<boolean> {\n\t\tconst count = await this.prisma.tag.count({\n\t\t\twhere: TagMapper.toPartialPersistence(where),\n\t\t\ttake: 1,\n\t\t})\n\n\t\treturn count > 0\n\t}"
+        ));
+    }
+
+    #[test]
+    fn extra_imports_are_added_to_their_file_type() {
+        let files = target(
+            vec![ModuleType::Repository(Some(vec![
+                RepositoryOperations::Create,
+            ]))],
+            &model("Tag"),
+            &config(
+                r#"{ "extraImports": { "prismaRepository": ["import { Logger } from '@nestjs/common'"] } }"#,
+            ),
+        );
+
+        assert!(file(
+            &files,
+            "/project/src/infra/database/prisma/prisma-tag.repository.ts"
+        )
+        .contains("import { Logger } from '@nestjs/common'\n"));
+        assert!(
+            !file(&files, "/project/src/app/repositories/tag.repository.ts").contains("Logger")
+        );
+    }
+
+    #[test]
+    fn mapped_models_are_accessed_by_their_prisma_names() {
+        let model = model("Account");
+        let repository =
+            prisma_repository(&model, vec![RepositoryOperations::Find], &Config::default());
+        let mapper = create_mapper(&model, &Config::default());
+
+        // `@@map("accounts")` and `@map("email_address")` only rename the
+        // table and column, Prisma Client keeps the model and field names.
+        assert!(repository.contains("this.prisma.account.findFirst("));
+        assert!(!repository.contains("this.prisma.accounts"));
+        assert!(mapper.contains("\n\t\t\temail: data.email,\n"));
+        assert!(mapper.contains("\n\t\t\temail: entity.email,\n"));
+        assert!(!mapper.contains("email_address"));
+        assert!(!mapper.contains("accounts"));
+    }
+
+    #[test]
+    fn unique_finders_skip_soft_deleted_rows() {
+        let repository = prisma_repository(
+            &model("User"),
+            vec![RepositoryOperations::FindByUnique],
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "this.prisma.user.findFirst({\n\t\t\twhere: {\n\t\t\t\temail,\n\t\t\t\tdeletedAt: null,\n\t\t\t},\n\t\t})"
+        ));
+    }
+
+    #[test]
+    fn prisma_service_connects_on_module_init() {
+        let service = create_prisma_service(&Config::default());
+
+        assert!(service
+            .contains("export class PrismaService extends PrismaClient implements OnModuleInit {"));
+        assert!(service.contains("async onModuleInit() {\n\t\tawait this.$connect()\n\t}"));
+    }
+
+    #[test]
+    fn defaulted_fields_are_optional_in_the_entity_constructor() {
+        let entity = create_entity(&model("Account"), true, &Config::default());
+
+        assert!(entity.contains(
+            "constructor(account: Omit<IAccount, 'createdAt'> & Partial<Pick<IAccount, 'createdAt'>>) {"
+        ));
+        assert!(entity.contains("\n\t\tthis.createdAt = account.createdAt ?? new Date()\n"));
+    }
+
+    #[test]
+    fn transforms_rewrite_the_written_contents() {
+        let path = std::env::temp_dir().join("entity-generator-transform/user.entity.ts");
+        let transforms: Vec<Transform> = vec![Box::new(|_, contents| {
+            contents.replace("@marker", "@MARKER")
+        })];
+
+        write_to_module(
+            &path,
+            "// @marker\nexport class User {}\n".to_string(),
+            &transforms,
+            OverwritePolicy::Always,
+            WriteMode::Disk,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "// @MARKER\nexport class User {}\n"
+        );
+    }
+
+    #[test]
+    fn existing_files_are_reported_as_skipped() {
+        let path = std::env::temp_dir().join("entity-generator-skip/user.entity.ts");
+        let write = |overwrite| {
+            write_to_module(
+                &path,
+                "export class User {}\n".to_string(),
+                &[],
+                overwrite,
+                WriteMode::Disk,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(write(OverwritePolicy::Always), WriteOutcome::Written);
+        assert_eq!(write(OverwritePolicy::SkipExisting), WriteOutcome::Skipped);
+        assert_eq!(write(OverwritePolicy::IfChanged), WriteOutcome::Skipped);
+    }
+
+    #[test]
+    fn json_fields_are_left_out_of_the_filter_type() {
+        let (repository, _) = create_repository(
+            &model("Setting"),
+            Some(vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::Create,
+            ]),
+            true,
+            true,
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "abstract find(data: Partial<Omit<Setting, 'payload'>>): Promise<Setting | null>"
+        ));
+        assert!(repository.contains("abstract create(data: Partial<Setting>): Promise<Setting>"));
+    }
+
+    #[test]
+    fn custom_code_survives_regeneration() {
+        let path = std::env::temp_dir().join("entity-generator-custom-code/tag.repository.ts");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "class A {\n\told() {}\n\t// <custom>\n\tmine() {}\n\t// </custom>\n}\n",
+        )
+        .unwrap();
+
+        let merged = merge_custom_code(
+            path.to_str().unwrap(),
+            "class A {\n\tnew() {}\n\t// <custom>\n\t// </custom>\n}\n".to_string(),
+        );
+
+        assert_eq!(
+            merged,
+            "class A {\n\tnew() {}\n\t// <custom>\n\tmine() {}\n\t// </custom>\n}\n"
+        );
+    }
+
+    #[test]
+    fn sensitive_fields_are_left_out_of_to_json() {
+        let models = parse_schema(
+            "model Member {\n  id String @id\n  email String\n  /// @sensitive\n  password String\n}\n"
+                .as_bytes(),
+        );
+        let entity = create_entity(&models[0], true, &Config::default());
+
+        assert!(entity.contains(
+            "export interface IMember {\n\tid: string\n\temail: string\n\tpassword: string\n}"
+        ));
+        assert!(entity.contains(
+            "toJSON(): Omit<IMember, 'password'> {\n\t\tconst { password, ...rest } = this\n\t\treturn rest\n\t}"
+        ));
+    }
+
+    #[test]
+    fn types_barrel_exports_every_generated_entity() {
+        let dir = std::env::temp_dir().join("entity-generator-types-barrel");
+        let _ = fs::remove_dir_all(&dir);
+        let config = config(r#"{ "typesBarrel": true }"#);
+
+        for name in ["User", "Tag"] {
+            write_modules(
+                vec![OutputTarget {
+                    modules: vec![ModuleType::Entity],
+                    module_path: "src/".to_string(),
+                }],
+                &dir,
+                &model(name),
+                &config,
+                &[],
+                WriteMode::Disk,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(dir.join("src/domain/entity/index.ts")).unwrap(),
+            "export * from './tag.entity'\nexport * from './user.entity'\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("src/types.generated.ts")).unwrap(),
+            "export * as Entities from './domain/entity'\n"
+        );
+    }
+
+    #[test]
+    fn increment_updates_the_field_atomically() {
+        let repository = prisma_repository(
+            &model("Account"),
+            vec![RepositoryOperations::Increment],
+            &Config::default(),
+        );
+
+        assert!(repository.contains("async increment<K extends 'balance'>("));
+        assert!(repository.contains("data: {\n\t\t\t\t[field]: { increment: by },\n\t\t\t},"));
+    }
+
+    #[test]
+    fn reserved_field_names_are_renamed_and_mapped_back() {
+        let models = parse_schema(
+            "model Part {\n  id String @id\n  constructor String\n  price Decimal\n}\n".as_bytes(),
+        );
+        let part = &models[0];
+        let entity = create_entity(part, true, &Config::default());
+        let mapper = create_mapper(part, &Config::default());
+        let repository = prisma_repository(
+            part,
+            vec![RepositoryOperations::Create, RepositoryOperations::Find],
+            &Config::default(),
+        );
+
+        assert!(entity.contains("\n\treadonly constructor_: string\n"));
+        assert!(mapper.contains("\n\t\t\tconstructor_: data.constructor,\n"));
+        assert!(mapper.contains(
+            "static toPartialPersistence(entity: Partial<Part>): Partial<PrismaPart> {\n\t\treturn {\n\t\t\tid: entity.id,\n\t\t\tconstructor: entity.constructor_,\n\t\t\tprice: entity.price != null ? new Prisma.Decimal(entity.price) : entity.price,\n\t\t}\n\t}"
+        ));
+        assert!(repository.contains("data: PartMapper.toPartialPersistence(data),"));
+        assert!(repository.contains("where: PartMapper.toPartialPersistence(data),"));
+    }
+
+    #[test]
+    fn find_random_skips_a_random_number_of_counted_rows() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::FindRandom],
+            &Config::default(),
+        );
+
+        assert!(repository.contains("const total = await this.prisma.tag.count()\n"));
+        assert!(repository.contains(
+            "this.prisma.tag.findFirst({\n\t\t\tskip: Math.floor(Math.random() * total),\n\t\t})"
+        ));
+    }
+
+    #[test]
+    fn props_style_entities_expose_getters() {
+        let entity = create_entity(
+            &model("Tag"),
+            true,
+            &config(r#"{ "entityStyle": "props" }"#),
+        );
+
+        assert!(entity.contains("\n\tprivate readonly props: ITag\n"));
+        assert!(entity.contains("\n\tget id(): number {\n\t\treturn this.props.id\n\t}\n"));
+        assert!(entity.contains("\n\tget name(): string {\n\t\treturn this.props.name\n\t}\n"));
+    }
+
+    #[test]
+    fn string_decimals_keep_their_precision() {
+        let config = config(r#"{ "decimalType": "string" }"#);
+
+        assert!(create_entity(&model("Account"), true, &config).contains("\n\tbalance: string\n"));
+        assert!(create_mapper(&model("Account"), &config)
+            .contains("\n\t\t\tbalance: data.balance.toString(),\n"));
+    }
+
+    #[test]
+    fn ownership_field_gets_an_is_owned_by_helper() {
+        let models = parse_schema(
+            "model Note {\n  id String @id\n  authorId String\n  body String\n}\n".as_bytes(),
+        );
+        let entity = create_entity(
+            &models[0],
+            true,
+            &config(r#"{ "models": { "Note": { "ownershipField": "authorId" } } }"#),
+        );
+
+        assert!(entity.contains(
+            "\tstatic isOwnedBy(entity: Note, authorId: string): boolean {\n\t\treturn entity.authorId === authorId\n\t}"
+        ));
+    }
+
+    #[test]
+    fn reads_retry_the_configured_number_of_attempts() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::Find],
+            &config(r#"{ "retry": { "attempts": 4, "backoffMs": 50 } }"#),
+        );
+
+        assert!(repository.contains("await this.withRetry(() => this.prisma.tag.findFirst({"));
+        assert!(repository.contains("for (let attempt = 1; ; attempt++) {"));
+        assert!(
+            repository.contains("if (attempt >= 4 || !['P1001', 'P1017'].includes(code ?? '')) {")
+        );
+        assert!(repository.contains("setTimeout(resolve, 50 * attempt)"));
+    }
+
+    #[test]
+    fn mapper_constructs_the_discriminated_subclasses() {
+        let models = parse_schema(
+            "model User {\n  id   String @id @default(uuid())\n  type String\n  name String\n}\n"
+                .as_bytes(),
+        );
+        let config = config(
+            r#"{
+                "models": {
+                    "User": {
+                        "discriminator": {
+                            "field": "type",
+                            "classes": { "admin": "AdminUser", "guest": "GuestUser" }
+                        }
+                    }
+                }
+            }"#,
+        );
+        let files = target(vec![ModuleType::Mapper], &models[0], &config);
+        let mapper = file(
+            &files,
+            "/project/src/infra/database/prisma/mappers/user.mapper.ts",
+        );
+
+        assert!(mapper.contains("switch (data.type) {"));
+        assert!(mapper.contains("case 'admin':\n\t\t\t\treturn new AdminUser(props)"));
+        assert!(mapper.contains("case 'guest':\n\t\t\t\treturn new GuestUser(props)"));
+        assert!(mapper.contains("default:\n\t\t\t\treturn new User(props)"));
+        assert!(mapper
+            .contains("import { AdminUser } from '../../../../domain/entity/admin-user.entity'"));
+        assert!(mapper
+            .contains("import { GuestUser } from '../../../../domain/entity/guest-user.entity'"));
+    }
+
+    #[test]
+    fn stream_pages_through_batches() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::FindManyStream],
+            &config(r#"{ "streamBatchSize": 250 }"#),
+        );
+
+        assert!(
+            repository.contains("async *findManyStream(data: Partial<Tag>): AsyncGenerator<Tag> {")
+        );
+        assert!(repository.contains("const batchSize = 250"));
+        assert!(repository.contains("for (let skip = 0; ; skip += batchSize) {"));
+        assert!(repository.contains("skip,\n\t\t\t\ttake: batchSize,"));
+        assert!(repository.contains("yield* batch.map(TagMapper.toDomain)"));
+    }
+
+    #[test]
+    fn mapper_preserves_null_optional_numbers() {
+        let models = parse_schema(
+            "model Stat {\n  id    Int     @id @default(autoincrement())\n  views Int?\n  ratio Float?\n  big   BigInt?\n}\n"
+                .as_bytes(),
+        );
+        let mapper = create_mapper(&models[0], &config("{}"));
+
+        assert!(mapper.contains("views: data.views ?? null,"));
+        assert!(mapper.contains("ratio: data.ratio ?? null,"));
+        assert!(mapper.contains("big: data.big != null ? Number(data.big) : null,"));
+    }
+
+    #[test]
+    fn each_target_is_written_under_its_own_path() {
+        let dir = std::env::temp_dir().join("entity-generator-multiple-targets");
+        let _ = fs::remove_dir_all(&dir);
+
+        write_modules(
+            vec![
+                OutputTarget {
+                    modules: vec![ModuleType::Entity, ModuleType::Mapper],
+                    module_path: "src/".to_string(),
+                },
+                OutputTarget {
+                    modules: vec![ModuleType::Entity],
+                    module_path: "test/".to_string(),
+                },
+            ],
+            &dir,
+            &model("Tag"),
+            &Config::default(),
+            &[],
+            WriteMode::Disk,
+        )
+        .unwrap();
+
+        assert!(dir.join("src/domain/entity/tag.entity.ts").is_file());
+        assert!(dir
+            .join("src/infra/database/prisma/mappers/tag.mapper.ts")
+            .is_file());
+        assert!(dir.join("test/domain/entity/tag.entity.ts").is_file());
+        assert!(!dir
+            .join("test/infra/database/prisma/mappers/tag.mapper.ts")
+            .exists());
+    }
+
+    #[test]
+    fn refresh_rebuilds_the_entity_through_the_mapper() {
+        let entity = create_entity(&model("Tag"), true, &Config::default());
+
+        assert!(entity
+            .contains("refresh(data: PrismaTag): Tag {\n\t\treturn TagMapper.toDomain(data)\n\t}"));
+    }
+
+    #[test]
+    fn temporal_instants_are_converted_from_dates() {
+        let config = config(r#"{ "dateTimeType": "instant" }"#);
+        let account = model("Account");
+
+        assert!(
+            create_entity(&account, true, &config).contains("readonly createdAt: Temporal.Instant")
+        );
+        assert!(create_mapper(&account, &config)
+            .contains("createdAt: Temporal.Instant.from(data.createdAt.toISOString()),"));
+    }
+
+    #[test]
+    fn cached_reads_check_and_populate_the_cache() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::Find, RepositoryOperations::Update],
+            &config(r#"{ "cache": { "ttlMs": 30000, "keyPrefix": "tags" } }"#),
+        );
+
+        assert!(repository.contains("@Inject(CACHE_MANAGER) private readonly cache: Cache"));
+        assert!(repository.contains(
+            "await this.cached(`tags:find:${JSON.stringify(data)}`, () => this.prisma.tag.findFirst({"
+        ));
+        assert!(repository.contains("const cached = await this.cache.get<T>(key)"));
+        assert!(repository.contains("await this.cache.set(key, result, 30000)"));
+        assert!(repository
+            .contains("await this.cache.set('tags:keys', [...new Set([...keys, key])], 30000)"));
+        assert!(repository.contains("await this.evicting(() => this.prisma.tag.update({"));
+        assert!(repository.contains(
+            "await Promise.all([...keys, 'tags:keys'].map((key) => this.cache.del(key)))"
+        ));
+        assert!(!repository.contains("store.keys()"));
+    }
+
+    #[test]
+    fn find_signatures_match_their_nullability() {
+        let operations = || {
+            vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::FindMany,
+                RepositoryOperations::FindByUnique,
+            ]
+        };
+        let (repository, prisma_repository) = create_repository(
+            &model("User"),
+            Some(operations()),
+            true,
+            true,
+            &Config::default(),
+        );
+
+        assert!(repository.contains("abstract find(data: Partial<User>): Promise<User | null>"));
+        assert!(repository.contains("abstract findMany(data: Partial<User>): Promise<User[]>"));
+        assert!(repository.contains("abstract findByEmail(email: string): Promise<User | null>"));
+        assert!(
+            prisma_repository.contains("async find(data: Partial<User>): Promise<User | null> {")
+        );
+        assert!(prisma_repository.contains("return result ? UserMapper.toDomain(result) : null"));
+
+        let (repository, prisma_repository) = create_repository(
+            &model("User"),
+            Some(operations()),
+            true,
+            true,
+            &config(r#"{ "throwOnNotFound": true }"#),
+        );
+
+        assert!(repository.contains("abstract find(data: Partial<User>): Promise<User>"));
+        assert!(repository.contains("abstract findByEmail(email: string): Promise<User>"));
+        assert!(prisma_repository.contains("this.prisma.user.findFirstOrThrow({"));
+        assert!(
+            prisma_repository.contains("async findMany(data: Partial<User>): Promise<User[]> {")
+        );
+    }
+
+    #[test]
+    fn example_comments_become_jsdoc_tags() {
+        let models = parse_schema(
+            "model Tag {\n  id   Int    @id @default(autoincrement())\n  /// @example typescript\n  name String\n}\n"
+                .as_bytes(),
+        );
+        let entity = create_entity(&models[0], false, &Config::default());
+
+        assert!(entity.contains("\t/** @example typescript */\n\treadonly name: string"));
+    }
+
+    #[test]
+    fn class_suffix_renames_classes_and_files() {
+        let files = target(
+            vec![ModuleType::Entity, ModuleType::Mapper],
+            &model("Tag"),
+            &config(r#"{ "classSuffix": "Entity" }"#),
+        );
+
+        assert!(
+            file(&files, "/project/src/domain/entity/tag-entity.entity.ts")
+                .contains("export class TagEntity implements ITagEntity {")
+        );
+        assert!(file(
+            &files,
+            "/project/src/infra/database/prisma/mappers/tag-entity.mapper.ts"
+        )
+        .contains("export class TagEntityMapper {"));
+    }
+
+    #[test]
+    fn delete_returns_the_mapped_entity() {
+        let config = config(r#"{ "deleteReturnsEntity": true }"#);
+        let repository =
+            prisma_repository(&model("Tag"), vec![RepositoryOperations::Delete], &config);
+
+        assert!(repository.contains("const result = await this.prisma.tag.delete({"));
+        assert!(repository.contains("return TagMapper.toDomain(result)"));
+
+        let repository =
+            prisma_repository(&model("User"), vec![RepositoryOperations::Delete], &config);
+
+        assert!(repository.contains("async softDelete(id: string): Promise<User> {"));
+        assert!(repository.contains("data: {\n\t\t\t\tdeletedAt: new Date(),\n\t\t\t},"));
+    }
+
+    #[test]
+    fn plain_profile_emits_no_nestjs_decorators() {
+        let config = config(r#"{ "decorators": "plain" }"#);
+        let service = create_prisma_service(&config);
+        let repository =
+            prisma_repository(&model("Tag"), vec![RepositoryOperations::Find], &config);
+
+        assert_eq!(
+            service,
+            "import { PrismaClient } from '@prisma/client'\n\nexport class PrismaService extends PrismaClient {}\n"
+        );
+        assert!(!repository.contains("@Injectable"));
+        assert!(!repository.contains("@Inject("));
+    }
+
+    #[test]
+    fn list_query_dto_paginates_and_sorts_by_field() {
+        let dto = create_list_query_dto(&model("Tag"), &Config::default());
+
+        assert!(dto.contains("@Min(1)\n\tpage?: number"));
+        assert!(dto.contains("@Min(1)\n\tpageSize?: number"));
+        assert!(dto.contains("@IsIn(['id', 'name'])\n\tsortBy?: 'id' | 'name'"));
+        assert!(dto.contains("@IsIn(['asc', 'desc'])\n\torder?: 'asc' | 'desc'"));
+    }
+
+    #[test]
+    fn report_lists_the_skipped_fields() {
+        let models = parse_schema(
+            "model Place {\n  id       Int                      @id @default(autoincrement())\n  location Unsupported(\"geometry\")\n  owner    User                     @relation(fields: [ownerId], references: [id])\n  ownerId  String\n}\n"
+                .as_bytes(),
+        );
+        let report: serde_json::Value = serde_json::from_str(&create_report(
+            &models,
+            &std::env::temp_dir().join("entity-generator-empty-report"),
+            "src/",
+            &Config::default(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            report,
+            serde_json::json!([{
+                "name": "Place",
+                "fieldCount": 4,
+                "generatedModules": [],
+                "skippedFields": [
+                    { "name": "location", "fieldType": "Unsupported(\"geometry\")" },
+                    { "name": "owner", "fieldType": "User" }
+                ]
+            }])
+        );
+    }
+
+    #[test]
+    fn tenant_scope_is_merged_into_every_query() {
+        let config = config(
+            r#"{
+                "models": {
+                    "Tag": {
+                        "scope": {
+                            "fields": ["name"],
+                            "context": "TenantContext",
+                            "module": "../../../tenancy/tenant.context"
+                        }
+                    }
+                }
+            }"#,
+        );
+        let files = target(
+            vec![ModuleType::Repository(Some(vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::FindMany,
+                RepositoryOperations::Count,
+            ]))],
+            &model("Tag"),
+            &config,
+        );
+        let repository = file(
+            &files,
+            "/project/src/infra/database/prisma/prisma-tag.repository.ts",
+        );
+
+        assert!(
+            repository.contains("import { TenantContext } from '../../../tenancy/tenant.context'")
+        );
+        assert!(repository.contains("private readonly context: TenantContext"));
+        assert_eq!(
+            repository
+                .matches("where: { ...data, ...this.scope },")
+                .count(),
+            3
+        );
+        assert!(repository.contains("\n\t\t\tname: this.context.name,"));
+    }
+
+    #[test]
+    fn contracts_only_emits_interfaces_and_abstract_repositories() {
+        let files = target(
+            vec![
+                ModuleType::Entity,
+                ModuleType::Mapper,
+                ModuleType::Repository(Some(vec![RepositoryOperations::Find])),
+                ModuleType::Dto,
+            ],
+            &model("Tag"),
+            &config(r#"{ "contractsOnly": true }"#),
+        );
+        let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+
+        assert_eq!(
+            paths,
+            [
+                "/project/src/domain/entity/tag.entity.ts",
+                "/project/src/app/repositories/tag.repository.ts",
+            ]
+        );
+        assert_eq!(
+            file(&files, "/project/src/domain/entity/tag.entity.ts"),
+            "export interface ITag {\n\tid: number\n\tname: string\n}\n"
+        );
+        assert!(
+            file(&files, "/project/src/app/repositories/tag.repository.ts")
+                .contains("abstract find(data: Partial<ITag>): Promise<ITag | null>")
+        );
+    }
+
+    #[test]
+    fn validated_constructor_throws_on_missing_required_fields() {
+        let entity = create_entity(
+            &model("User"),
+            false,
+            &config(r#"{ "validateConstruction": true }"#),
+        );
+
+        assert!(entity.contains(
+            "for (const key of ['email'] as const) {\n\t\t\tif (user[key] === undefined) {\n\t\t\t\tthrow new Error(`User.${key} is required`)\n\t\t\t}\n\t\t}"
+        ));
+    }
+
+    #[test]
+    fn find_many_forwards_distinct() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::FindMany],
+            &config(r#"{ "findMany": { "distinct": true } }"#),
+        );
+
+        assert!(repository.contains(
+            "async findMany(data: Partial<Tag>, distinct?: (keyof PrismaTag)[]): Promise<Tag[]> {"
+        ));
+        assert!(repository
+            .contains("where: TagMapper.toPartialPersistence(data),\n\t\t\tdistinct,\n\t\t})"));
+    }
+
+    #[test]
+    fn undefined_absent_value_normalizes_nulls() {
+        let config = config(r#"{ "absentValue": "undefined" }"#);
+        let user = model("User");
+
+        assert!(create_entity(&user, true, &config).contains("\tname: string | undefined\n"));
+
+        let mapper = create_mapper(&user, &config);
+
+        assert!(mapper.contains("name: data.name ?? undefined,"));
+        assert!(mapper.contains("name: entity.name ?? null,"));
+    }
+
+    #[test]
+    fn views_only_get_read_operations() {
+        let models =
+            parse_schema("view TagStat {\n  name  String @unique\n  count Int\n}\n".as_bytes());
+        let (repository, prisma_repository) = create_repository(
+            &models[0],
+            Some(vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::FindMany,
+                RepositoryOperations::Create,
+                RepositoryOperations::Update,
+                RepositoryOperations::Delete,
+            ]),
+            true,
+            true,
+            &Config::default(),
+        );
+
+        assert!(
+            repository.contains("abstract find(data: Partial<TagStat>): Promise<TagStat | null>")
+        );
+        assert!(
+            repository.contains("abstract findMany(data: Partial<TagStat>): Promise<TagStat[]>")
+        );
+
+        for method in [
+            "create(",
+            "update(",
+            "delete(",
+            "softDelete(",
+            "hardDelete(",
+        ] {
+            assert!(!repository.contains(method), "{}", method);
+            assert!(!prisma_repository.contains(method), "{}", method);
+        }
+    }
+
+    #[test]
+    fn metadata_header_is_prepended_once() {
+        let header = metadata_header(Path::new("prisma/schema.prisma"));
+        let path = Path::new("src/domain/entity/tag.entity.ts");
+        let contents = header(path, &header(path, "export class Tag {}\n"));
+        let version = format!(
+            "// Generated by entity-generator v{}\n",
+            env!("CARGO_PKG_VERSION")
+        );
+
+        assert_eq!(
+            contents,
+            format!(
+                "{}// Source: prisma/schema.prisma\n\nexport class Tag {{}}\n",
+                version
+            )
+        );
+        assert_eq!(contents.matches(&version).count(), 1);
+    }
+
+    #[test]
+    fn selected_fields_are_returned_as_rows() {
+        let (repository, prisma_repository) = create_repository(
+            &model("User"),
+            Some(vec![RepositoryOperations::FindMany]),
+            true,
+            true,
+            &config(r#"{ "models": { "User": { "selectFields": ["id", "email"] } } }"#),
+        );
+
+        assert!(repository.contains(
+            "abstract findMany(data: Partial<User>): Promise<Pick<PrismaUser, 'id' | 'email'>[]>"
+        ));
+        assert!(prisma_repository
+            .contains("select: {\n\t\t\t\tid: true,\n\t\t\t\temail: true,\n\t\t\t},\n\t\t})"));
+        assert!(!prisma_repository.contains("UserMapper.toDomain"));
+
+        let (repository, _) = create_repository(
+            &model("User"),
+            Some(vec![RepositoryOperations::FindMany]),
+            true,
+            true,
+            &config(
+                r#"{ "contractsOnly": true, "models": { "User": { "selectFields": ["id", "email"] } } }"#,
+            ),
+        );
+
+        assert!(repository.contains("Promise<Pick<IUser, 'id' | 'email'>[]>"));
+    }
+
+    #[test]
+    fn builder_has_a_setter_per_field() {
+        let entity = create_entity(
+            &model("User"),
+            false,
+            &config(r#"{ "entityBuilder": true }"#),
+        );
+
+        for setter in [
+            "setId(id: string): this {",
+            "setEmail(email: string): this {",
+            "setName(name: string | null): this {",
+            "setDeletedAt(deletedAt: Date | null): this {",
+        ] {
+            assert!(entity.contains(setter), "{}", setter);
+        }
+        assert!(entity.contains(
+            "build(): User {\n\t\treturn new User(this.props as ConstructorParameters<typeof User>[0])\n\t}"
+        ));
+    }
+
+    #[test]
+    fn getter_access_routes_queries_through_the_client() {
+        let repository = prisma_repository(
+            &model("User"),
+            vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::Create,
+                RepositoryOperations::Count,
+                RepositoryOperations::FindAndCount,
+            ],
+            &config(r#"{ "prismaAccess": "getter" }"#),
+        );
+
+        assert!(repository
+            .contains("protected get client(): PrismaService {\n\t\treturn this.prisma\n"));
+        assert!(repository.contains("this.client.user.findFirst({"));
+        assert!(repository.contains("this.client.$transaction(["));
+        assert_eq!(repository.matches("this.prisma").count(), 1);
+    }
+
+    #[test]
+    fn aggregate_forwards_the_requested_aggregations() {
+        let repository = prisma_repository(
+            &model("Account"),
+            vec![RepositoryOperations::Aggregate],
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "async aggregate<K extends 'balance'>(where: Partial<Account>, options: { sum?: K[]; avg?: K[]; min?: K[]; max?: K[] })"
+        ));
+        assert!(repository.contains(
+            "this.prisma.account.aggregate({\n\t\t\twhere: AccountMapper.toPartialPersistence(where),\n\t\t\t_sum: select(options.sum),\n\t\t\t_avg: select(options.avg),\n\t\t\t_min: select(options.min),\n\t\t\t_max: select(options.max),\n\t\t})"
+        ));
+    }
+
+    #[test]
+    fn regenerating_a_model_hits_the_template_cache() {
+        let tag = model("Tag");
+        let mut templates = TemplateCache::default();
+        let mut renders = 0;
+        let mut render = |config: &Config| {
+            templates.render("entity", &tag, config, true, || {
+                renders += 1;
+                create_entity(&tag, true, config)
+            })
+        };
+        let models = r#"{ "models": { "Tag": {}, "User": {}, "Account": {}, "Post": {} } }"#;
+
+        let first = render(&config(models));
+        // Parsed again, the models map iterates in an order of its own.
+        let second = render(&config(models));
+        render(&config(r#"{ "classPrefix": "Cached" }"#));
+
+        assert_eq!(first, second);
+        assert_eq!(renders, 2);
+    }
+
+    #[test]
+    fn mapper_uses_the_field_transform() {
+        let mapper = create_mapper(
+            &model("User"),
+            &config(
+                r#"{ "models": { "User": { "transforms": { "email": "data.email.toLowerCase()" } } } }"#,
+            ),
+        );
+
+        assert!(mapper.contains("\t\t\temail: data.email.toLowerCase(),\n\t\t\tname: data.name,"));
+    }
+
+    #[test]
+    fn repository_registry_types_every_generated_repository() {
+        let dir = std::env::temp_dir().join("entity-generator-repository-registry");
+        let _ = fs::remove_dir_all(&dir);
+        let config = Config::default();
+        let models = [model("User"), model("Tag"), model("Account")];
+
+        for model in &models[..2] {
+            write_modules(
+                vec![OutputTarget {
+                    modules: vec![ModuleType::Repository(Some(vec![
+                        RepositoryOperations::Find,
+                    ]))],
+                    module_path: "src/".to_string(),
+                }],
+                &dir,
+                model,
+                &config,
+                &[],
+                WriteMode::Disk,
+            )
+            .unwrap();
+        }
+
+        let registry = create_repository_registry(&models, &dir, "src/", &config);
+
+        assert!(registry.contains(
+            "export type RepositoryRegistry = {\n\tUser: UserRepository\n\tTag: TagRepository\n}"
+        ));
+        assert!(registry.contains("import { TagRepository } from './tag.repository'"));
+        assert!(!registry.contains("Account"));
+    }
+
+    #[test]
+    fn update_without_a_mapper_updates_by_id() {
+        let (_, repository) = create_repository(
+            &model("Tag"),
+            Some(vec![RepositoryOperations::Update]),
+            false,
+            true,
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "return this.prisma.tag.update({\n\t\t\twhere: {\n\t\t\t\tid,\n\t\t\t},\n\t\t\tdata,\n\t\t})"
+        ));
+        assert!(!repository.contains("findMany"));
+    }
+
+    #[test]
+    fn static_schema_lists_every_field() {
+        let entity = create_entity(
+            &model("User"),
+            false,
+            &config(r#"{ "staticSchema": true }"#),
+        );
+
+        assert!(entity.contains(
+            "\tstatic readonly schema = [\n\t\t{ name: 'id', type: 'String', optional: false },\n\t\t{ name: 'email', type: 'String', optional: false },\n\t\t{ name: 'name', type: 'String', optional: true },\n\t\t{ name: 'deletedAt', type: 'DateTime', optional: true },\n\t] as const"
+        ));
+    }
+
+    #[test]
+    fn esm_imports_use_js_extensions() {
+        let files = target(
+            vec![ModuleType::Entity, ModuleType::Mapper],
+            &model("Tag"),
+            &config(r#"{ "moduleSystem": "esm" }"#),
+        );
+        let mapper = file(
+            &files,
+            "/project/src/infra/database/prisma/mappers/tag.mapper.ts",
+        );
+
+        assert!(mapper.contains("import { Tag } from '../../../../domain/entity/tag.entity.js'"));
+        assert!(mapper.contains("import { Tag as PrismaTag } from '@prisma/client'"));
+    }
+
+    #[test]
+    fn dtos_implement_the_write_model_and_keep_renamed_fields() {
+        let models = parse_schema(
+            "model Part {\n  id String @id\n  constructor String\n  meta Json?\n}\n".as_bytes(),
+        );
+        let dto = create_dto(&models[0], &config(r#"{ "readWriteModels": true }"#));
+
+        assert!(dto.contains("import { PartWriteModel } from '../../domain/entity/part.entity'"));
+        assert!(
+            dto.contains("export class CreatePartDto implements Omit<PartWriteModel, 'meta'> {")
+        );
+        assert!(dto.contains(
+            "export class UpdatePartDto implements Partial<Omit<PartWriteModel, 'meta'>> {"
+        ));
+        assert!(dto.contains("\n\tconstructor_: string"));
+    }
+
+    #[test]
+    fn find_and_count_uses_a_window_function_on_postgres() {
+        let models = parse_schema(
+            "datasource db {\n\tprovider = \"postgresql\"\n\turl      = env(\"DATABASE_URL\")\n}\n\nmodel Tag {\n\tid   Int    @id @default(autoincrement())\n\tname String\n}\n"
+                .as_bytes(),
+        );
+        let config = config(r#"{ "findAndCount": "windowFunction" }"#);
+        let repository = prisma_repository(
+            &models[0],
+            vec![RepositoryOperations::FindAndCount],
+            &config,
+        );
+
+        assert!(
+            repository.contains("await this.prisma.$queryRaw<(PrismaTag & { total: bigint })[]>`")
+        );
+        assert!(repository
+            .contains("SELECT \"id\", \"name\", COUNT(*) OVER() AS \"total\"\n\t\t\tFROM \"Tag\""));
+        assert!(!repository.contains("$transaction"));
+
+        // Other providers keep the transaction.
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::FindAndCount],
+            &config,
+        );
+
+        assert!(repository.contains("this.prisma.$transaction(["));
+        assert!(!repository.contains("$queryRaw"));
+    }
+
+    #[test]
+    fn structural_equality_compares_every_field() {
+        let entity = create_entity(
+            &model("User"),
+            false,
+            &config(r#"{ "equality": "structural" }"#),
+        );
+
+        assert!(entity.contains(
+            "equals(other: User): boolean {\n\t\treturn (\n\t\t\tthis.id === other.id &&\n\t\t\tthis.email === other.email &&\n\t\t\tthis.name === other.name &&\n\t\t\tthis.deletedAt?.getTime() === other.deletedAt?.getTime()\n\t\t)\n\t}"
+        ));
+    }
+
+    #[test]
+    fn id_parameters_use_the_id_property_type() {
+        let operations = vec![
+            RepositoryOperations::Update,
+            RepositoryOperations::Delete,
+            RepositoryOperations::Upsert,
+            RepositoryOperations::Increment,
+        ];
+        let (repository, prisma_repository) = create_repository(
+            &model("Account"),
+            Some(operations.clone()),
+            true,
+            true,
+            &Config::default(),
+        );
+
+        for method in [
+            "update(id: number,",
+            "delete(id: number)",
+            "upsert(id: number,",
+            "increment<K extends 'balance'>(id: number,",
+        ] {
+            assert!(repository.contains(method), "{}", method);
+            assert!(prisma_repository.contains(method), "{}", method);
+        }
+
+        let config = config(r#"{ "brandedIds": true }"#);
+        let (repository, prisma_repository) = create_repository(
+            &model("User"),
+            Some(vec![
+                RepositoryOperations::Update,
+                RepositoryOperations::Delete,
+                RepositoryOperations::Restore,
+                RepositoryOperations::FindByUnique,
+            ]),
+            true,
+            true,
+            &config,
+        );
+
+        for method in [
+            "update(id: UserId,",
+            "softDelete(id: UserId)",
+            "restore(id: UserId)",
+        ] {
+            assert!(repository.contains(method), "{}", method);
+            assert!(prisma_repository.contains(method), "{}", method);
+        }
+        assert!(!repository.contains("id: string"));
+        assert!(!prisma_repository.contains("id: string"));
+        assert!(repository.contains("abstract findByEmail(email: string): Promise<User | null>"));
+    }
+
+    #[test]
+    fn json_schema_requires_fields_and_maps_types() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&create_json_schema(&model("User"), &Config::default())).unwrap();
+
+        assert_eq!(schema["properties"]["email"], json!({ "type": "string" }));
+        assert_eq!(
+            schema["properties"]["deletedAt"],
+            json!({ "anyOf": [{ "type": "string", "format": "date-time" }, { "type": "null" }] })
+        );
+        assert_eq!(
+            schema["required"],
+            json!(["id", "email", "name", "deletedAt"])
+        );
+    }
+
+    #[test]
+    fn delete_many_rejects_an_empty_where_unless_allowed() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::DeleteMany],
+            &Config::default(),
+        );
+
+        assert!(repository.contains(
+            "this.prisma.tag.deleteMany({\n\t\t\twhere: TagMapper.toPartialPersistence(this.sanitizeWhere('deleteMany', where, options)),"
+        ));
+        assert!(repository.contains(
+            "if (conditions.length === 0 && !options?.allowEmptyWhere) {\n\t\t\tthrow new Error("
+        ));
+    }
+
+    #[test]
+    fn delete_many_soft_deletes_and_keeps_the_scope_out_of_the_guard() {
+        let config = config(
+            r#"{ "models": { "User": { "scope": { "fields": ["name"], "module": "../../../common/query-context" } } } }"#,
+        );
+        let repository = prisma_repository(
+            &model("User"),
+            vec![
+                RepositoryOperations::DeleteMany,
+                RepositoryOperations::UpdateMany,
+            ],
+            &config,
+        );
+
+        assert!(repository.contains("this.prisma.user.updateMany({\n\t\t\twhere: { ...UserMapper.toPartialPersistence(this.sanitizeWhere('deleteMany', where, options)), ...this.scope, deletedAt: null },\n\t\t\tdata: {\n\t\t\t\tdeletedAt: new Date(),"));
+        assert!(repository.contains("where: { ...UserMapper.toPartialPersistence(this.sanitizeWhere('updateMany', where, options)), ...this.scope, deletedAt: null },\n\t\t\tdata: UserMapper.toPartialPersistence(data),"));
+        assert_eq!(repository.matches("protected sanitizeWhere").count(), 1);
+    }
+
+    #[test]
+    fn imports_follow_the_configured_group_order() {
+        let contents = "import { User } from './user.entity'\nimport { Injectable } from '@nestjs/common'\nimport { Money } from '@/shared/money'\nimport { Tag } from './tag.entity'\n\nexport class A {}\n";
+        let ordered = order_imports(
+            contents.to_string(),
+            &config(r#"{ "importOrder": ["relative", "internal", "external"] }"#),
+        );
+
+        assert_eq!(
+            ordered,
+            "import { User } from './user.entity'\nimport { Tag } from './tag.entity'\nimport { Money } from '@/shared/money'\nimport { Injectable } from '@nestjs/common'\n\nexport class A {}\n"
+        );
+    }
+
+    #[test]
+    fn filter_helpers_match_by_field_and_date_range() {
+        let helpers = build_filter_helpers(&model("Account"), &Config::default());
+
+        assert!(
+            helpers.contains("byEmail: (email: string): Prisma.AccountWhereInput => ({ email }),")
+        );
+        assert!(helpers.contains(
+            "createdAfter: (date: Date): Prisma.AccountWhereInput => ({ createdAt: { gt: date } }),"
+        ));
+        assert!(helpers.contains(
+            "createdBefore: (date: Date): Prisma.AccountWhereInput => ({ createdAt: { lt: date } }),"
+        ));
+    }
+
+    #[test]
+    fn spaces_indent_every_generated_level() {
+        let files = target(
+            vec![
+                ModuleType::Entity,
+                ModuleType::Mapper,
+                ModuleType::Repository(Some(vec![RepositoryOperations::Find])),
+                ModuleType::JsonSchema,
+            ],
+            &model("Tag"),
+            &config(r#"{ "indentStyle": { "spaces": 4 } }"#),
+        );
+
+        assert!(file(&files, "/project/src/domain/entity/tag.entity.ts")
+            .contains("export class Tag implements ITag {\n    readonly id: number\n"));
+        assert!(
+            file(&files, "/project/src/infra/database/prisma/mappers/tag.mapper.ts").contains(
+                "static toDomain(data: PrismaTag): Tag {\n        return new Tag({\n            id: data.id,"
+            )
+        );
+        assert!(
+            file(&files, "/project/src/infra/database/prisma/prisma-tag.repository.ts").contains(
+                "    async find(data: Partial<Tag>): Promise<Tag | null> {\n        const result = await this.prisma.tag.findFirst({\n            where: TagMapper.toPartialPersistence(data),\n        })"
+            )
+        );
+        // The JSON Schema is indented the way serde_json prints it.
+        assert!(file(&files, "/project/src/domain/schemas/tag.schema.json")
+            .contains("{\n  \"$schema\""));
+    }
+
+    #[test]
+    fn custom_code_keeps_its_indentation_across_regenerations() {
+        let dir = std::env::temp_dir().join("entity-generator-custom-code-indent");
+        let _ = fs::remove_dir_all(&dir);
+        let config = config(r#"{ "preserveCustomCode": true, "indentStyle": { "spaces": 4 } }"#);
+        let generate = || {
+            write_modules(
+                vec![OutputTarget {
+                    modules: vec![ModuleType::Repository(Some(vec![
+                        RepositoryOperations::Find,
+                    ]))],
+                    module_path: "src/".to_string(),
+                }],
+                &dir,
+                &model("Tag"),
+                &config,
+                &[],
+                WriteMode::Disk,
+            )
+            .unwrap()
+        };
+        let path = dir.join("src/infra/database/prisma/prisma-tag.repository.ts");
+        let custom_code =
+            "    // <custom>\n    mine() {\n        return 1\n    }\n    // </custom>";
+
+        generate();
+        let generated = fs::read_to_string(&path).unwrap();
+        let start = generated.find("    // <custom>").unwrap();
+        let end = generated.find("// </custom>").unwrap() + "// </custom>".len();
+        fs::write(
+            &path,
+            format!(
+                "{}{}{}",
+                &generated[..start],
+                custom_code,
+                &generated[end..]
+            ),
+        )
+        .unwrap();
+
+        generate();
+        generate();
+
+        assert!(fs::read_to_string(&path).unwrap().contains(custom_code));
+    }
+
+    #[test]
+    fn prisma_repository_snapshot() {
+        let dir = std::env::temp_dir().join("entity-generator-repository-snapshot");
+        let _ = fs::remove_dir_all(&dir);
+
+        write_modules(
+            vec![OutputTarget {
+                modules: vec![
+                    ModuleType::Entity,
+                    ModuleType::Mapper,
+                    ModuleType::Repository(Some(vec![
+                        RepositoryOperations::Create,
+                        RepositoryOperations::Find,
+                        RepositoryOperations::FindMany,
+                        RepositoryOperations::Update,
+                        RepositoryOperations::Delete,
+                    ])),
+                ],
+                module_path: "src/".to_string(),
+            }],
+            &dir,
+            &model("Tag"),
+            &Config::default(),
+            &[],
+            WriteMode::Disk,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("src/infra/database/prisma/prisma-tag.repository.ts"))
+                .unwrap(),
+            r#"import { Injectable } from '@nestjs/common'
+import { Tag } from '../../../domain/entity/tag.entity'
+import { TagMapper } from './mappers/tag.mapper'
+import { TagRepository } from '../../../app/repositories/tag.repository'
+import { PrismaService } from './prisma.service'
+
+@Injectable()
+export class PrismaTagRepository implements TagRepository {
+	constructor(private readonly prisma: PrismaService) {}
+	async create(data: Partial<Tag>): Promise<Tag> {
+		const result = await this.prisma.tag.create({
+			data: TagMapper.toPartialPersistence(data),
+		})
+
+		return TagMapper.toDomain(result)
+	}
+	async find(data: Partial<Tag>): Promise<Tag | null> {
+		const result = await this.prisma.tag.findFirst({
+			where: TagMapper.toPartialPersistence(data),
+		})
+
+		return result ? TagMapper.toDomain(result) : null
+	}
+	async findMany(data: Partial<Tag>): Promise<Tag[]> {
+		const result = await this.prisma.tag.findMany({
+			where: TagMapper.toPartialPersistence(data),
+		})
+
+		return result.map(TagMapper.toDomain)
+	}
+	async update(id: number, data: Partial<Tag>): Promise<Tag> {
+		const result = await this.prisma.tag.update({
+			where: {
+				id,
+			},
+			data: TagMapper.toPartialPersistence(data),
+		})
+
+		return TagMapper.toDomain(result)
+	}
+	async delete(id: number) {
+		await this.prisma.tag.delete({
+			where: {
+				id,
+			},
+		})
+	}
+}
+"#
+        );
+    }
+
+    #[test]
+    fn mikro_orm_entity_decorates_the_key_and_properties() {
+        let entity = create_mikro_orm_entity(&model("User"), &Config::default());
+
+        assert!(
+            entity.contains("@PrimaryKey({ type: 'string' })\n\tid: string = crypto.randomUUID()")
+        );
+        assert!(entity.contains("@Property({ type: 'string' })\n\temail!: string"));
+        assert!(entity.contains("@Property({ type: 'string', nullable: true })\n\tname?: string"));
+    }
+
+    #[test]
+    fn soft_delete_follows_the_deleted_at_column() {
+        let operations = || vec![RepositoryOperations::Find, RepositoryOperations::Delete];
+        let user = prisma_repository(&model("User"), operations(), &Config::default());
+        let tag = prisma_repository(&model("Tag"), operations(), &Config::default());
+
+        assert!(is_soft_delete(&model("User")));
+        assert!(user.contains("deletedAt: null"));
+        assert!(user.contains("async softDelete(id: string)"));
+
+        assert!(!is_soft_delete(&model("Tag")));
+        assert!(!tag.contains("deletedAt"));
+        assert!(tag.contains("this.prisma.tag.delete({"));
+    }
+
+    #[test]
+    fn mapper_translates_mapped_enum_values() {
+        let models = parse_schema(
+            "enum Role {\n  ADMIN\n  SUPER_ADMIN\n}\n\nmodel Staff {\n  id   String @id\n  role Role\n}\n"
+                .as_bytes(),
+        );
+        let mapper = create_mapper(
+            &models[0],
+            &config(r#"{ "enumValues": { "Role": { "ADMIN": "Admin" } } }"#),
+        );
+
+        assert!(mapper.contains(
+            "export const RoleToDomain = { ADMIN: 'Admin', SUPER_ADMIN: 'SUPER_ADMIN' } as const"
+        ));
+        assert!(mapper.contains("role: RoleToDomain[data.role],"));
+        assert!(mapper.contains("role: RoleToPersistence[entity.role],"));
+    }
+
+    #[test]
+    fn every_read_leaves_soft_deleted_rows_out() {
+        let models = parse_schema(
+            "datasource db {\n\tprovider = \"postgresql\"\n\turl      = env(\"DATABASE_URL\")\n}\n\nmodel Post {\n\tid        String    @id\n\ttitle     String\n\tviews     Int\n\tdeletedAt DateTime?\n}\n"
+                .as_bytes(),
+        );
+        let post = &models[0];
+        let repository = prisma_repository(
+            post,
+            vec![
+                RepositoryOperations::Search,
+                RepositoryOperations::Exists,
+                RepositoryOperations::FindRandom,
+                RepositoryOperations::Aggregate,
+                RepositoryOperations::FindManyStream,
+                RepositoryOperations::FindAndCount,
+                RepositoryOperations::DeleteMany,
+                RepositoryOperations::UpdateMany,
+            ],
+            &config(r#"{ "models": { "Post": { "searchableFields": ["title"] } } }"#),
+        );
+
+        assert!(repository.contains("],\n\t\t\t\tdeletedAt: null,\n\t\t\t},\n\t\t\t...pagination,"));
+        assert!(repository.contains("this.prisma.post.count({ where: { deletedAt: null } })"));
+        assert!(repository.contains(
+            "where: { deletedAt: null },\n\t\t\tskip: Math.floor(Math.random() * total),"
+        ));
+        assert_eq!(
+            repository
+                .matches("where: { ...PostMapper.toPartialPersistence(where), deletedAt: null },")
+                .count(),
+            2
+        );
+        assert_eq!(
+            repository
+                .matches("where: { ...PostMapper.toPartialPersistence(data), deletedAt: null },")
+                .count(),
+            3
+        );
+        assert!(repository
+            .contains("this.sanitizeWhere('deleteMany', where, options)), deletedAt: null },"));
+        assert!(repository
+            .contains("this.sanitizeWhere('updateMany', where, options)), deletedAt: null },"));
+
+        // The raw window count bypasses the client extension.
+        let repository = prisma_repository(
+            post,
+            vec![RepositoryOperations::FindAndCount],
+            &config(r#"{ "findAndCount": "windowFunction", "softDeleteExtension": {} }"#),
+        );
+
+        assert!(repository.contains(
+            "Object.entries({ ...PostMapper.toPartialPersistence(data), deletedAt: null })"
+        ));
+    }
+
+    #[test]
+    fn optional_dto_fields_reject_null() {
+        let dto = create_dto(
+            &model("User"),
+            &config(r#"{ "dtoNullability": "optional" }"#),
+        );
+
+        assert!(dto.contains(
+            "@ValidateIf((_, value) => value !== undefined)\n\t@IsString()\n\tname?: string\n"
+        ));
+        assert!(!dto.contains("| null"));
+    }
+
+    #[test]
+    fn integration_spec_finds_the_created_record_by_id() {
+        let spec = create_integration_spec(
+            &model("User"),
+            Some(&[
+                RepositoryOperations::Create,
+                RepositoryOperations::Find,
+                RepositoryOperations::Delete,
+            ]),
+            &config(r#"{ "findById": true }"#),
+        );
+
+        assert!(spec.contains("beforeEach(async () => {\n\t\tawait prisma.user.deleteMany()\n\t})"));
+        assert!(spec.contains(
+            "const created = await repository.create(data)\n\n\t\tconst found = await repository.findById(created.id)\n\n\t\texpect(found?.id).toEqual(created.id)"
+        ));
+        assert!(spec.contains("await repository.softDelete(created.id)\n\n\t\texpect(await repository.findById(created.id)).toBeNull()"));
+    }
+
+    #[test]
+    fn date_dto_fields_are_transformed_from_strings() {
+        let models = parse_schema(
+            "model Event {\n  id       String   @id\n  startsAt DateTime\n}\n".as_bytes(),
+        );
+        let dto = create_dto(&models[0], &Config::default());
+
+        assert!(dto.starts_with("import { Transform } from 'class-transformer'\n"));
+        assert!(dto
+            .contains("@Transform(({ value }) => new Date(value))\n\t@IsDate()\n\tstartsAt: Date"));
+        assert!(dto.contains(
+            "@Transform(({ value }) => (value == null ? value : new Date(value)))\n\t@IsOptional()\n\t@IsDate()\n\tstartsAt?: Date"
+        ));
+    }
+
+    #[test]
+    fn cache_decorators_cache_reads_and_evict_on_mutations() {
+        let files = target(
+            vec![ModuleType::Repository(Some(vec![
+                RepositoryOperations::Find,
+                RepositoryOperations::Create,
+                RepositoryOperations::Update,
+            ]))],
+            &model("User"),
+            &config(
+                r#"{ "cacheDecorators": { "module": "@acme/cache", "ttl": 60, "keyPrefix": "user" } }"#,
+            ),
+        );
+        let repository = file(
+            &files,
+            "/project/src/infra/database/prisma/prisma-user.repository.ts",
+        );
+
+        assert!(repository.contains("import { Cacheable, CacheEvict } from '@acme/cache'"));
+        assert!(repository.contains(
+            "@Cacheable({ key: (...args: unknown[]) => `user:find:${JSON.stringify(args)}`, ttl: 60 })\n\tasync find("
+        ));
+        assert!(repository.contains("@CacheEvict({ key: 'user:*' })\n\tasync create("));
+        assert!(repository.contains("@CacheEvict({ key: 'user:*' })\n\tasync update("));
+    }
+
+    #[test]
+    fn mutations_emit_their_typed_events() {
+        let config = config(r#"{ "events": {} }"#);
+        let operations = vec![
+            RepositoryOperations::Create,
+            RepositoryOperations::Upsert,
+            RepositoryOperations::CreateMany,
+        ];
+        let files = target(
+            vec![
+                ModuleType::Entity,
+                ModuleType::Events,
+                ModuleType::Repository(Some(operations.clone())),
+            ],
+            &model("Tag"),
+            &config,
+        );
+
+        assert!(file(&files, "/project/src/domain/events/tag.events.ts")
+            .contains("export interface TagCreatedEvent {\n\ttag: Tag\n}"));
+
+        let repository = file(
+            &files,
+            "/project/src/infra/database/prisma/prisma-tag.repository.ts",
+        );
+
+        assert!(repository
+            .contains("this.events.emit('tag.created', { tag } satisfies TagCreatedEvent)"));
+        assert_eq!(repository.matches("this.events.emit(").count(), 1);
+
+        let spec = create_integration_spec(&model("Tag"), Some(&operations), &config);
+
+        assert!(spec.contains("new PrismaTagRepository(prisma, { emit: () => true } as never)"));
+    }
+
+    #[test]
+    fn upper_enum_value_casing_is_translated_by_the_mapper() {
+        let models = parse_schema(
+            "enum Role {\n  Admin\n  SuperAdmin\n}\n\nmodel Staff {\n  id   String @id\n  role Role\n}\n"
+                .as_bytes(),
+        );
+        let config = config(r#"{ "enumValueCasing": "upper" }"#);
+
+        assert!(create_entity(&models[0], true, &config)
+            .starts_with("export type Role = 'ADMIN' | 'SUPERADMIN'\n"));
+        assert!(create_mapper(&models[0], &config).contains(
+            "export const RoleToDomain = { Admin: 'ADMIN', SuperAdmin: 'SUPERADMIN' } as const"
+        ));
+    }
+
+    #[test]
+    fn context_models_are_nested_under_their_module() {
+        let models = parse_schema(
+            "/// @context billing\nmodel Invoice {\n  id    String @id\n  total Int\n}\n"
+                .as_bytes(),
+        );
+        let files = target(vec![ModuleType::Entity], &models[0], &Config::default());
+        let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+
+        assert_eq!(
+            paths,
+            ["/project/src/modules/billing/domain/entity/invoice.entity.ts"]
+        );
+    }
+
+    #[test]
+    fn type_guard_checks_each_field_type() {
+        let entity = create_entity(&model("User"), false, &config(r#"{ "typeGuards": true }"#));
+
+        assert!(entity.contains("export function isUser(value: unknown): value is IUser {"));
+        assert!(entity.contains("typeof record.email === 'string' &&"));
+        assert!(entity.contains("(record.name == null || typeof record.name === 'string') &&"));
+        assert!(entity.contains("(record.deletedAt == null || record.deletedAt instanceof Date)"));
+    }
+
+    #[test]
+    fn mapper_converts_decimals_in_both_directions() {
+        let mapper = create_mapper(&model("Account"), &config("{}"));
+
+        assert!(mapper.contains("static toDomain(data: PrismaAccount): Account {"));
+        assert!(mapper.contains("balance: Number(data.balance),"));
+        assert!(mapper.contains("static toPersistence(entity: Account): PrismaAccount {"));
+        assert!(mapper.contains("balance: new Prisma.Decimal(entity.balance),"));
+    }
+
+    #[test]
+    fn soft_delete_extension_filters_deleted_rows() {
+        let extension =
+            create_soft_delete_extension(&parse_schema(SCHEMA.as_bytes()), IndentStyle::Tabs);
+
+        assert!(extension.contains("const SOFT_DELETE_MODELS: string[] = ['User']"));
+        for operation in ["findFirst", "findMany"] {
+            assert!(
+                extension.contains(&format!("async {}({{ model, args, query }}) {{", operation))
+            );
+        }
+        assert!(extension.contains("args.where = { ...args.where, deletedAt: null }"));
+    }
+
+    #[test]
+    fn find_many_translates_the_sort_spec_into_order_by() {
+        let repository = prisma_repository(
+            &model("Tag"),
+            vec![RepositoryOperations::FindMany],
+            &config(r#"{ "findMany": { "sort": true } }"#),
+        );
+
+        assert!(repository.contains(
+            "findMany(data: Partial<Tag>, sort?: { field: keyof PrismaTag; direction: 'asc' | 'desc' }[])"
+        ));
+        assert!(repository
+            .contains("orderBy: sort?.map(({ field, direction }) => ({ [field]: direction })),"));
+    }
+
+    #[test]
+    fn constants_export_the_field_tuple_and_delegate() {
+        let constants = create_constants(&model("Account"), &config("{}"));
+
+        assert!(constants.contains(
+            "export const ACCOUNT_FIELDS = ['id', 'email', 'balance', 'createdAt'] as const"
+        ));
+        assert!(constants.contains("export const ACCOUNT_DELEGATE = 'account'"));
+    }
+
+    #[test]
+    fn soft_delete_model_gets_soft_and_hard_delete() {
+        let (abstract_repository, repository) = create_repository(
+            &model("User"),
+            Some(vec![RepositoryOperations::Delete]),
+            true,
+            true,
+            &config("{}"),
+        );
+
+        assert!(abstract_repository.contains("abstract softDelete(id: string): Promise<void>"));
+        assert!(abstract_repository.contains("abstract hardDelete(id: string): Promise<void>"));
+        assert!(repository.contains(
+            "async softDelete(id: string) {\n\t\tawait this.prisma.user.update({\n\t\t\twhere: {\n\t\t\t\tid,\n\t\t\t},\n\t\t\tdata: {\n\t\t\t\tdeletedAt: new Date(),\n\t\t\t},\n\t\t})\n\t}"
+        ));
+        assert!(repository.contains(
+            "async hardDelete(id: string) {\n\t\tawait this.prisma.user.delete({\n\t\t\twhere: {\n\t\t\t\tid,\n\t\t\t},\n\t\t})\n\t}"
+        ));
     }
 }
@@ -0,0 +1,457 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+const CONFIG_FILE: &str = "entity-generator.json";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    pub repository_factory: bool,
+    pub filter_helpers: bool,
+    pub find_by_id: bool,
+    pub read_write_models: bool,
+    pub export_style: ExportStyle,
+    pub module_system: ModuleSystem,
+    pub decorators: DecoratorProfile,
+    pub class_prefix: String,
+    pub class_suffix: String,
+    pub extra_imports: ExtraImports,
+    pub indent_style: IndentStyle,
+    pub import_order: Vec<ImportGroup>,
+    pub internal_import_prefixes: Option<Vec<String>>,
+    pub preserve_custom_code: bool,
+    pub overwrite: OverwritePolicy,
+    pub metadata_header: bool,
+    pub types_barrel: bool,
+    pub contracts_only: bool,
+    pub report: bool,
+    pub repository_registry: bool,
+    pub reserved_name_suffix: Option<String>,
+    pub entity_style: EntityStyle,
+    pub validate_construction: bool,
+    pub entity_builder: bool,
+    pub type_guards: bool,
+    pub static_schema: bool,
+    pub branded_ids: bool,
+    pub equality: Option<Equality>,
+    pub decimal_type: DecimalType,
+    pub json_type: Option<String>,
+    pub bytes_type: BytesType,
+    pub date_time_type: DateTimeType,
+    pub absent_value: AbsentValue,
+    pub dto_nullability: Option<DtoNullability>,
+    pub prisma_access: PrismaAccess,
+    pub throw_on_not_found: bool,
+    pub delete_returns_entity: bool,
+    pub retry: Option<RetryConfig>,
+    pub cache: Option<CacheConfig>,
+    pub cache_decorators: Option<CacheDecoratorsConfig>,
+    pub events: Option<EventsConfig>,
+    pub soft_delete_extension: Option<SoftDeleteExtensionConfig>,
+    pub stream_batch_size: Option<u32>,
+    pub find_many: FindManyConfig,
+    pub find_and_count: FindAndCountStrategy,
+    pub enum_values: HashMap<String, BTreeMap<String, String>>,
+    pub enum_value_casing: EnumValueCasing,
+    pub models: HashMap<String, ModelConfig>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtraImports {
+    pub entity: Vec<String>,
+    pub mapper: Vec<String>,
+    pub repository: Vec<String>,
+    pub prisma_repository: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FindManyConfig {
+    pub distinct: bool,
+    pub pagination: bool,
+    pub order_by: bool,
+    pub sort: bool,
+}
+
+/// How `findAndCount` fetches a page of rows together with the total.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindAndCountStrategy {
+    /// A `findMany` and a `count` run in one transaction.
+    #[default]
+    Transaction,
+    /// A single raw query counting the rows with `COUNT(*) OVER()`. Only
+    /// used with the `postgresql` provider.
+    WindowFunction,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetryConfig {
+    pub attempts: u32,
+    pub backoff_ms: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            attempts: 3,
+            backoff_ms: 100,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CacheConfig {
+    pub ttl_ms: u32,
+    pub key_prefix: Option<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            ttl_ms: 60_000,
+            key_prefix: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndentStyle {
+    #[default]
+    Tabs,
+    Spaces(usize),
+}
+
+impl IndentStyle {
+    /// Whitespace for `level` levels of indentation.
+    pub fn indent(&self, level: usize) -> String {
+        match self {
+            IndentStyle::Tabs => "\t".repeat(level),
+            IndentStyle::Spaces(width) => " ".repeat(width * level),
+        }
+    }
+}
+
+/// Kind of module an import points at, used to group the imports of the
+/// generated files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportGroup {
+    /// Packages from `node_modules`, e.g. `@nestjs/common`.
+    External,
+    /// Path aliases of the project, e.g. `@/domain/user`.
+    Internal,
+    /// Paths starting with `.`.
+    Relative,
+}
+
+/// What to do when a generated file already exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverwritePolicy {
+    #[default]
+    Always,
+    SkipExisting,
+    /// Only rewrites files whose contents would change.
+    IfChanged,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheDecoratorsConfig {
+    /// Package exporting the `Cacheable` and `CacheEvict` decorators.
+    pub module: String,
+    pub ttl: Option<u32>,
+    pub key_prefix: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EventsConfig {
+    /// Directory of the event payload files, under the module path.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SoftDeleteExtensionConfig {
+    /// Directory of the extension file, under the module path.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntityStyle {
+    #[default]
+    Fields,
+    Props,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Equality {
+    Id,
+    Structural,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DecimalType {
+    #[default]
+    Number,
+    String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BytesType {
+    #[default]
+    Buffer,
+    Uint8Array,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AbsentValue {
+    #[default]
+    Null,
+    Undefined,
+}
+
+/// How the DTOs type and validate optional fields, when it shouldn't follow
+/// the entity's absent value.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DtoNullability {
+    /// `field?: T | null`, accepting explicit nulls.
+    Nullable,
+    /// `field?: T`, rejecting explicit nulls.
+    Optional,
+}
+
+/// Casing of the domain enum values, for the values without an explicit
+/// `enumValues` entry.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum EnumValueCasing {
+    #[default]
+    Original,
+    Upper,
+    Lower,
+    PascalCase,
+}
+
+impl EnumValueCasing {
+    fn apply(&self, value: &str) -> String {
+        match self {
+            EnumValueCasing::Original => value.to_string(),
+            EnumValueCasing::Upper => value.to_uppercase(),
+            EnumValueCasing::Lower => value.to_lowercase(),
+            EnumValueCasing::PascalCase => value
+                .split(['_', '-'])
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first
+                            .to_uppercase()
+                            .chain(chars.flat_map(char::to_lowercase))
+                            .collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateTimeType {
+    #[default]
+    Date,
+    Instant,
+    PlainDateTime,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DecoratorProfile {
+    #[default]
+    #[serde(rename = "nestjs")]
+    NestJs,
+    Plain,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleSystem {
+    #[default]
+    #[serde(rename = "commonjs")]
+    CommonJs,
+    Esm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrismaAccess {
+    #[default]
+    Field,
+    Getter,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportStyle {
+    #[default]
+    Named,
+    Default,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ModelConfig {
+    pub searchable_fields: Vec<String>,
+    pub sensitive_fields: Vec<String>,
+    pub select_fields: Vec<String>,
+    pub transforms: BTreeMap<String, String>,
+    pub ownership_field: Option<String>,
+    pub discriminator: Option<DiscriminatorConfig>,
+    pub scope: Option<ScopeConfig>,
+    /// Bounded context the model's files are nested under, overriding a
+    /// `/// @context` comment.
+    pub context: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeConfig {
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Injectable class holding the scope values.
+    #[serde(default = "ScopeConfig::default_context")]
+    pub context: String,
+    /// Module exporting the `context` class.
+    pub module: String,
+}
+
+impl ScopeConfig {
+    fn default_context() -> String {
+        "QueryContext".to_string()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscriminatorConfig {
+    pub field: String,
+    pub classes: BTreeMap<String, String>,
+}
+
+impl Config {
+    pub fn load(dir: &Path) -> Result<Config, String> {
+        let path = dir.join(CONFIG_FILE);
+
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) => return Err(format!("{}: {}", path.display(), err)),
+        };
+
+        serde_json::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn reserved_name_suffix(&self) -> &str {
+        self.reserved_name_suffix.as_deref().unwrap_or("_")
+    }
+
+    /// TypeScript type of `Json` fields. Contracts can't depend on the Prisma
+    /// client types, so they fall back to `unknown`.
+    pub fn json_type(&self) -> &str {
+        match &self.json_type {
+            Some(json_type) => json_type,
+            None if self.contracts_only => "unknown",
+            None => "Prisma.JsonValue",
+        }
+    }
+
+    /// Order of the import groups at the top of the generated files. Groups
+    /// missing from the configured order come last.
+    pub fn import_order(&self) -> &[ImportGroup] {
+        if self.import_order.is_empty() {
+            &[
+                ImportGroup::External,
+                ImportGroup::Internal,
+                ImportGroup::Relative,
+            ]
+        } else {
+            &self.import_order
+        }
+    }
+
+    pub fn internal_import_prefixes(&self) -> Vec<&str> {
+        match &self.internal_import_prefixes {
+            Some(prefixes) => prefixes.iter().map(String::as_str).collect(),
+            None => vec!["@/", "~/"],
+        }
+    }
+
+    pub fn stream_batch_size(&self) -> u32 {
+        self.stream_batch_size.unwrap_or(100)
+    }
+
+    /// Name of the generated classes for a model, with the configured
+    /// prefix and suffix.
+    pub fn class_name(&self, model_name: &str) -> String {
+        format!("{}{}{}", self.class_prefix, model_name, self.class_suffix)
+    }
+
+    /// Domain name of a schema enum value, when it differs from the database
+    /// value.
+    pub fn domain_enum_value(&self, enum_name: &str, value: &str) -> String {
+        self.enum_values
+            .get(enum_name)
+            .and_then(|values| values.get(value))
+            .cloned()
+            .unwrap_or_else(|| self.enum_value_casing.apply(value))
+    }
+
+    pub fn translates_enum(&self, enum_name: &str) -> bool {
+        self.enum_value_casing != EnumValueCasing::Original
+            || self
+                .enum_values
+                .get(enum_name)
+                .is_some_and(|values| !values.is_empty())
+    }
+
+    pub fn model(&self, name: &str) -> Option<&ModelConfig> {
+        self.models.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_reports_malformed_config() {
+        let dir = std::env::temp_dir().join("entity-generator-malformed-config");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(CONFIG_FILE), "{ \"repositoryFactory\": }").unwrap();
+
+        let err = Config::load(&dir).unwrap_err();
+
+        assert!(err.starts_with(&dir.join(CONFIG_FILE).display().to_string()));
+    }
+}
@@ -1,17 +1,38 @@
-use code_gen::{write_modules, ModuleType, RepositoryOperations};
+use code_gen::{
+    metadata_header, write_modules, write_report, write_repository_registry,
+    write_soft_delete_extension, ModuleType, OutputTarget, RepositoryOperations, Transform,
+    WriteMode, WriteOutcome,
+};
+use config::Config;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, MultiSelect};
 use parser::{get_schemas, parse_schema, TsConfig};
 use std::{
     env,
     fs::{self, File},
     io::BufReader,
+    process,
 };
 
 mod code_gen;
+mod config;
 mod parser;
+#[cfg(feature = "validate-imports")]
+mod validate;
 
 fn main() {
     let dir = env::current_dir().unwrap();
+    let mode = if env::args().skip(1).any(|arg| arg == "--dry-run") {
+        WriteMode::Stdout
+    } else {
+        WriteMode::Disk
+    };
+    let config = match Config::load(&dir) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
     let schemas = get_schemas(format!("{}/prisma", dir.display())).unwrap();
 
     let schema_file_names: Vec<String> = schemas
@@ -30,7 +51,8 @@ fn main() {
         .interact()
         .unwrap();
 
-    let schema_file = File::open(schemas.get(schema_selection).unwrap()).unwrap();
+    let schema_path = schemas.get(schema_selection).unwrap();
+    let schema_file = File::open(schema_path).unwrap();
 
     let reader = BufReader::new(schema_file);
 
@@ -76,13 +98,22 @@ fn main() {
         .unwrap()
         .replace("*", "");
 
-    let multiselected: &[&str; 3] = &[
+    let multiselected: &[&str; 10] = &[
         ModuleType::Entity.into(),
         ModuleType::Mapper.into(),
         ModuleType::Repository(None).into(),
+        ModuleType::PrismaService.into(),
+        ModuleType::QueryDto.into(),
+        ModuleType::Dto.into(),
+        ModuleType::JsonSchema.into(),
+        ModuleType::MikroOrmEntity.into(),
+        ModuleType::IntegrationSpec.into(),
+        ModuleType::Constants.into(),
     ];
 
-    let defaults = &[true, false, false];
+    let defaults = &[
+        true, false, false, false, false, false, false, false, false, false,
+    ];
 
     let selections = MultiSelect::with_theme(&ColorfulTheme::default())
         .with_prompt("Select which classes to create")
@@ -93,16 +124,30 @@ fn main() {
 
     let mut selected_modules: Vec<ModuleType> = selections
         .iter()
-        .map(|i| ModuleType::from(*multiselected.get(*i).unwrap()))
+        .map(|i| ModuleType::try_from(*multiselected.get(*i).unwrap()).unwrap())
         .collect();
 
     if selected_modules.contains(&ModuleType::Repository(None)) {
-        let methods: &[RepositoryOperations; 5] = &[
+        let methods: &[RepositoryOperations; 19] = &[
             RepositoryOperations::Find,
             RepositoryOperations::FindMany,
+            RepositoryOperations::FindAndCount,
+            RepositoryOperations::Count,
             RepositoryOperations::Create,
+            RepositoryOperations::CreateMany,
             RepositoryOperations::Delete,
             RepositoryOperations::Update,
+            RepositoryOperations::Upsert,
+            RepositoryOperations::DeleteMany,
+            RepositoryOperations::UpdateMany,
+            RepositoryOperations::Search,
+            RepositoryOperations::Exists,
+            RepositoryOperations::FindByUnique,
+            RepositoryOperations::Increment,
+            RepositoryOperations::FindRandom,
+            RepositoryOperations::FindManyStream,
+            RepositoryOperations::Aggregate,
+            RepositoryOperations::Restore,
         ];
 
         let selections = MultiSelect::with_theme(&ColorfulTheme::default())
@@ -125,5 +170,68 @@ fn main() {
         selected_modules[index] = ModuleType::Repository(Some(selected_repositories))
     };
 
-    write_modules(selected_modules, &dir, &module_path, selected_model)
+    let target = OutputTarget {
+        modules: selected_modules,
+        module_path: module_path.clone(),
+    };
+
+    let mut transforms: Vec<Transform> = Vec::new();
+
+    if config.metadata_header {
+        transforms.push(metadata_header(
+            schema_path.strip_prefix(&dir).unwrap_or(schema_path),
+        ));
+    }
+
+    let result = write_modules(
+        vec![target],
+        &dir,
+        selected_model,
+        &config,
+        &transforms,
+        mode,
+    )
+    .and_then(|mut outcomes| {
+        if config.repository_registry {
+            outcomes.push(write_repository_registry(
+                &models,
+                &dir,
+                &module_path,
+                &config,
+                &transforms,
+                mode,
+            )?);
+        }
+
+        if config.soft_delete_extension.is_some() {
+            outcomes.push(write_soft_delete_extension(
+                &models,
+                &dir,
+                &module_path,
+                &config,
+                &transforms,
+                mode,
+            )?);
+        }
+
+        if config.report {
+            write_report(&models, &dir, &module_path, &config, mode)?;
+        }
+
+        Ok(outcomes)
+    });
+
+    match result {
+        Ok(outcomes) => {
+            for (path, outcome) in outcomes {
+                if outcome == WriteOutcome::Skipped {
+                    eprintln!("skipped {}", path);
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
 }
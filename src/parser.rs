@@ -1,9 +1,4 @@
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::{BufRead, BufReader},
-    path::PathBuf,
-};
+use std::{collections::HashMap, fs, io::BufRead, path::PathBuf};
 
 use serde::Deserialize;
 
@@ -18,29 +13,174 @@ pub struct TsConfig {
     pub compiler_options: TsConfigCompilerOptions,
 }
 
+#[derive(Debug)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Option<String>,
+}
+
+impl Attribute {
+    pub fn string_argument(&self) -> Option<&str> {
+        let arguments = self.arguments.as_deref()?;
+        let start = arguments.find('"')? + 1;
+        let end = start + arguments[start..].find('"')?;
+
+        Some(&arguments[start..end])
+    }
+}
+
 #[derive(Debug)]
 pub struct Field {
     pub name: String,
     pub field_type: String,
     pub is_optional: bool,
+    pub is_list: bool,
+    pub attributes: Vec<Attribute>,
+    pub documentation: Vec<String>,
+    /// Values of the schema enum the field's type refers to, empty for any
+    /// other type.
+    pub enum_values: Vec<String>,
+}
+
+impl Field {
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Database column backing this field, honoring `@map`.
+    pub fn column_name(&self) -> &str {
+        self.attribute("map")
+            .and_then(Attribute::string_argument)
+            .unwrap_or(&self.name)
+    }
+
+    pub fn is_enum(&self) -> bool {
+        !self.enum_values.is_empty()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tag_value(tag).is_some()
+    }
+
+    pub fn tag_value(&self, tag: &str) -> Option<&str> {
+        self.documentation.iter().find_map(|line| {
+            let rest = line.strip_prefix(tag)?;
+
+            if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                return Some(rest.trim());
+            }
+
+            None
+        })
+    }
 }
 
 #[derive(Debug)]
 pub struct Model {
     pub name: String,
     pub fields: Vec<Field>,
+    pub attributes: Vec<Attribute>,
+    /// Declared as a `view` block or documented with `/// @view`.
+    pub is_view: bool,
+    /// Provider of the schema's datasource, e.g. `postgresql`.
+    pub provider: Option<String>,
+    /// Bounded context the model belongs to, from a `/// @context` comment.
+    pub context: Option<String>,
+}
+
+impl Model {
+    /// Database table backing this model, honoring `@@map`.
+    pub fn table_name(&self) -> &str {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == "map")
+            .and_then(Attribute::string_argument)
+            .unwrap_or(&self.name)
+    }
+}
+
+fn parse_documentation(line: &str) -> Option<String> {
+    line.strip_prefix("///").map(|doc| doc.trim().to_string())
 }
 
-pub fn parse_schema(reader: BufReader<File>) -> Vec<Model> {
+pub fn parse_schema(reader: impl BufRead) -> Vec<Model> {
     let mut lines = reader.lines().peekable();
     let mut models = Vec::new();
+    let mut enums: HashMap<String, Vec<String>> = HashMap::new();
+    let mut provider = None;
+    let mut model_documentation = Vec::new();
 
     while let Some(Ok(line)) = lines.next() {
         let line = line.trim();
 
-        if line.starts_with("model") {
+        if let Some(doc) = parse_documentation(line) {
+            model_documentation.push(doc);
+            continue;
+        }
+
+        let documentation = std::mem::take(&mut model_documentation);
+        let keyword = line.split_whitespace().next();
+
+        if keyword == Some("datasource") {
+            for block_line in lines.by_ref() {
+                let Ok(block_line) = block_line else { break };
+                let block_line = block_line.trim();
+
+                if block_line == "}" {
+                    break;
+                }
+
+                if let Some(value) = block_line
+                    .strip_prefix("provider")
+                    .and_then(|rest| rest.trim_start().strip_prefix('='))
+                {
+                    provider = Some(value.trim().trim_matches('"').to_string());
+                }
+            }
+
+            continue;
+        }
+
+        if keyword == Some("enum") {
+            let enum_name = line.split_whitespace().nth(1).unwrap().to_string();
+            let mut values = Vec::new();
+
+            for value_line in lines.by_ref() {
+                let Ok(value_line) = value_line else { break };
+                let value_line = value_line.trim();
+
+                if value_line == "}" {
+                    break;
+                }
+
+                if value_line.is_empty()
+                    || value_line.starts_with("//")
+                    || value_line.starts_with('@')
+                {
+                    continue;
+                }
+
+                if let Some(value) = value_line.split_whitespace().next() {
+                    values.push(value.to_string());
+                }
+            }
+
+            enums.insert(enum_name, values);
+            continue;
+        }
+
+        if matches!(keyword, Some("model" | "view")) {
+            let is_view = keyword == Some("view") || documentation.iter().any(|doc| doc == "@view");
+            let context = documentation.iter().find_map(|doc| {
+                doc.strip_prefix("@context ")
+                    .map(|context| context.trim().to_string())
+            });
             let model_name = line.split_whitespace().nth(1).unwrap().to_string();
             let mut fields = Vec::new();
+            let mut attributes = Vec::new();
+            let mut field_documentation = Vec::new();
 
             while let Some(Ok(field_line)) = lines.peek() {
                 let field_line = field_line.trim();
@@ -49,7 +189,12 @@ pub fn parse_schema(reader: BufReader<File>) -> Vec<Model> {
                     break;
                 }
 
-                if let Some(field) = parse_field(field_line) {
+                if let Some(doc) = parse_documentation(field_line) {
+                    field_documentation.push(doc);
+                } else if let Some(block_attributes) = field_line.strip_prefix('@') {
+                    attributes.extend(parse_attributes(block_attributes));
+                } else if let Some(mut field) = parse_field(field_line) {
+                    field.documentation = std::mem::take(&mut field_documentation);
                     fields.push(field);
                 }
 
@@ -59,10 +204,25 @@ pub fn parse_schema(reader: BufReader<File>) -> Vec<Model> {
             models.push(Model {
                 name: model_name,
                 fields,
+                attributes,
+                is_view,
+                provider: None,
+                context,
             });
         }
     }
 
+    // The datasource and enums can be declared after the models using them.
+    for model in &mut models {
+        model.provider = provider.clone();
+    }
+
+    for field in models.iter_mut().flat_map(|model| model.fields.iter_mut()) {
+        if let Some(values) = enums.get(&field.field_type) {
+            field.enum_values = values.clone();
+        }
+    }
+
     models
 }
 
@@ -74,20 +234,82 @@ fn parse_field(line: &str) -> Option<Field> {
         let mut field_type = parts[1].to_string();
         let is_optional = field_type.ends_with('?');
 
+        let type_start = field_name.len() + line[field_name.len()..].find(parts[1])?;
+        let attributes = parse_attributes(&line[type_start + parts[1].len()..]);
+
         if is_optional {
             field_type.pop();
         }
 
+        let is_list = field_type.ends_with("[]");
+
+        if is_list {
+            field_type.truncate(field_type.len() - 2);
+        }
+
         return Some(Field {
             name: field_name,
             field_type,
             is_optional,
+            is_list,
+            attributes,
+            documentation: Vec::new(),
+            enum_values: Vec::new(),
         });
     }
 
     None
 }
 
+fn parse_attributes(line: &str) -> Vec<Attribute> {
+    let mut attributes = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '@' {
+            continue;
+        }
+
+        let mut name = String::new();
+        while let Some(&ch) = chars.peek() {
+            if !(ch.is_alphanumeric() || ch == '_' || ch == '.') {
+                break;
+            }
+            name.push(ch);
+            chars.next();
+        }
+
+        let mut arguments = None;
+        if chars.peek() == Some(&'(') {
+            chars.next();
+            let mut depth = 1;
+            let mut in_string = false;
+            let mut value = String::new();
+
+            for ch in chars.by_ref() {
+                match ch {
+                    '"' => in_string = !in_string,
+                    '(' if !in_string => depth += 1,
+                    ')' if !in_string => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                value.push(ch);
+            }
+
+            arguments = Some(value);
+        }
+
+        attributes.push(Attribute { name, arguments });
+    }
+
+    attributes
+}
+
 pub fn get_schemas(path: String) -> Result<Vec<PathBuf>, String> {
     let entries = match fs::read_dir(path) {
         Ok(entries) => entries,
@@ -0,0 +1,86 @@
+use std::{fs, path::Path};
+
+pub struct UnresolvedImport {
+    pub path: String,
+    pub source: String,
+}
+
+/// Relative import sources (`./`, `../`) found in a TypeScript file.
+fn relative_imports(contents: &str) -> Vec<&str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("import") || line.starts_with("export"))
+        .filter_map(|line| {
+            let quote_start = line.rfind(['\'', '"'])?;
+            let quote = line.as_bytes()[quote_start] as char;
+            let source_start = line[..quote_start].rfind(quote)? + 1;
+
+            Some(&line[source_start..quote_start])
+        })
+        .filter(|source| source.starts_with("./") || source.starts_with("../"))
+        .collect()
+}
+
+fn resolves(base: &Path, source: &str) -> bool {
+    let target = base.join(source);
+    let target = target.to_string_lossy();
+    let target = target.strip_suffix(".js").unwrap_or(&target);
+
+    [
+        format!("{}.ts", target),
+        format!("{}/index.ts", target),
+        target.to_string(),
+    ]
+    .iter()
+    .any(|candidate| Path::new(candidate).is_file())
+}
+
+/// Checks that every relative import in the generated files points at a file
+/// that was generated or already exists on disk.
+pub fn unresolved_imports(paths: &[String]) -> Vec<UnresolvedImport> {
+    let mut unresolved = Vec::new();
+
+    for path in paths {
+        let Ok(contents) = fs::read_to_string(path) else {
+            continue;
+        };
+        let base = Path::new(path).parent().unwrap_or(Path::new("."));
+
+        for source in relative_imports(&contents) {
+            if !resolves(base, source) {
+                unresolved.push(UnresolvedImport {
+                    path: path.clone(),
+                    source: source.to_string(),
+                });
+            }
+        }
+    }
+
+    unresolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_an_unresolved_import() {
+        let dir = std::env::temp_dir().join("entity-generator-unresolved-import");
+        fs::create_dir_all(dir.join("entity")).unwrap();
+        fs::write(dir.join("entity/user.entity.ts"), "export class User {}\n").unwrap();
+
+        let mapper = dir.join("user.mapper.ts");
+        fs::write(
+            &mapper,
+            "import { User } from './entity/user.entity'\nimport { UserId } from './entity/user-id'\n",
+        )
+        .unwrap();
+
+        let unresolved = unresolved_imports(&[mapper.display().to_string()]);
+
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].path, mapper.display().to_string());
+        assert_eq!(unresolved[0].source, "./entity/user-id");
+    }
+}